@@ -0,0 +1,101 @@
+use std::collections::{HashMap, HashSet};
+
+use symboscript_types::interpreter::*;
+
+use super::Interpreter;
+
+/// Kept scopes, generators and futures below which nothing is collected
+const MIN_COLLECT_AT: usize = 64;
+
+impl Interpreter {
+    /// Frees a scope that was left, a scope seen by closures is kept until they are gone
+    pub(super) fn release_scope(&mut self, scope: &str) {
+        if self.vault[scope].is_captured() {
+            self.detached.push(scope.to_owned());
+        } else {
+            self.remove_refs(scope);
+            self.vault.remove(scope);
+        }
+    }
+
+    /// Collects once the kept scopes, generators and futures doubled since the last collection
+    pub(super) fn maybe_collect(&mut self) {
+        if self.kept() < self.collect_at {
+            return;
+        }
+
+        self.collect_generators();
+        self.collect_futures();
+        self.collect_scopes();
+
+        self.collect_at = (self.kept() * 2).max(MIN_COLLECT_AT);
+    }
+
+    fn kept(&self) -> usize {
+        self.detached.len() + self.generators.len() + self.executor.future_count()
+    }
+
+    /// Frees the left scopes that are only seen by closures stored in left scopes, like a function
+    /// declared in the body of another one
+    fn collect_scopes(&mut self) {
+        let detached = std::mem::take(&mut self.detached);
+
+        let mut refs = detached
+            .iter()
+            .map(|scope| (scope.as_str(), self.vault[scope].closures.strong_count()))
+            .collect::<HashMap<_, _>>();
+
+        for scope in &detached {
+            for value in self.vault[scope].values.values() {
+                for_each_handle(value, &mut |handle| {
+                    if let Some(count) = refs.get_mut(handle.id.as_str()) {
+                        *count -= 1;
+                    }
+                });
+            }
+        }
+
+        // Scopes held from outside of the left scopes are kept with the scopes they see
+        let mut stack = refs
+            .iter()
+            .filter(|(_, count)| **count > 0)
+            .map(|(scope, _)| *scope)
+            .collect::<Vec<_>>();
+        let mut alive = HashSet::new();
+
+        while let Some(scope) = stack.pop() {
+            if !alive.insert(scope) {
+                continue;
+            }
+
+            for value in self.vault[scope].values.values() {
+                for_each_handle(value, &mut |handle| {
+                    if let Some((scope, _)) = refs.get_key_value(handle.id.as_str()) {
+                        stack.push(scope);
+                    }
+                });
+            }
+        }
+
+        let (kept, freed): (Vec<_>, Vec<_>) = detached
+            .iter()
+            .cloned()
+            .partition(|scope| alive.contains(scope.as_str()));
+
+        for scope in freed {
+            self.remove_refs(&scope);
+            self.vault.remove(&scope);
+        }
+
+        self.detached = kept;
+    }
+}
+
+/// Calls `f` with the scope handles of the closures in the value
+fn for_each_handle(value: &Value, f: &mut impl FnMut(&Handle)) {
+    match value {
+        Value::Function(closure) => closure.env.iter().for_each(f),
+        Value::Sequence(seq) => seq.iter().for_each(|item| for_each_handle(item, f)),
+        _ => {}
+    }
+}
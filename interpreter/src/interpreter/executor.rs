@@ -0,0 +1,298 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    rc::Weak,
+    thread,
+    time::{Duration, Instant},
+};
+
+use symboscript_types::{interpreter::*, parser::*};
+
+use super::{generator::ResumePoint, generator::Suspended, Interpreter, Interrupt, ThrowSite};
+
+/// State of a value produced by an async function or a timer
+pub enum FutureState {
+    /// Async function body waiting to be run by the executor
+    Task(Suspended),
+
+    /// Task being evaluated right now
+    Running,
+
+    /// Timer resolved once the deadline is reached
+    Sleep(Instant),
+
+    Resolved(Value),
+
+    /// Task ended with a throw, it is thrown again where the future is awaited
+    Failed(Value),
+}
+
+/// Single threaded cooperative executor of async tasks
+#[derive(Default)]
+pub struct Executor {
+    /// Futures by id, with a reference that tells whether the program still holds them
+    futures: HashMap<String, (Weak<()>, FutureState)>,
+
+    /// Tasks ready to run, in order
+    ready: VecDeque<String>,
+
+    /// Tasks waiting for a future, by the id of the future
+    waiting: HashMap<String, Vec<String>>,
+
+    /// Failed tasks that are not awaited yet with the site of their throw, in order
+    unhandled: Vec<(String, Option<ThrowSite>)>,
+}
+
+impl Executor {
+    /// Number of futures, settled ones included
+    pub fn future_count(&self) -> usize {
+        self.futures.len()
+    }
+}
+
+/// Results of the calls made by the current statement of a task
+#[derive(Default)]
+pub struct Replay {
+    values: Vec<Value>,
+    pos: usize,
+}
+
+impl Interpreter {
+    /// Schedules the body of an async function, it is run by the executor
+    pub(super) fn new_task(&mut self, closure: &Closure) -> Value {
+        let task = self.suspend_call(closure);
+
+        let handle = Handle::new(self.gen_id());
+        self.executor.futures.insert(
+            handle.id.clone(),
+            (handle.downgrade(), FutureState::Task(task)),
+        );
+        self.executor.ready.push_back(handle.id.clone());
+        self.maybe_collect();
+
+        Value::Future(handle)
+    }
+
+    /// `None` when the deadline can't be represented, like for infinite durations
+    pub(super) fn new_sleep(&mut self, ms: f64) -> Option<Value> {
+        let duration = Duration::try_from_secs_f64(ms.max(0.0) / 1000.0).ok()?;
+        let deadline = Instant::now().checked_add(duration)?;
+
+        let handle = Handle::new(self.gen_id());
+        self.executor.futures.insert(
+            handle.id.clone(),
+            (handle.downgrade(), FutureState::Sleep(deadline)),
+        );
+        self.maybe_collect();
+
+        Some(Value::Future(handle))
+    }
+
+    /// Suspends the current task until the future is resolved, outside of tasks the executor is driven until then
+    pub(super) fn eval_await(&mut self, word_expr: &WordExpression) -> Value {
+        // The handle keeps the future while the executor is driven
+        let future = match self.eval_expression(&word_expr.argument) {
+            Value::Future(future) => future,
+            // Awaiting anything else gives the value back
+            value => return value,
+        };
+        let id = future.id.clone();
+
+        if self.interrupt.is_some() {
+            return Value::None;
+        }
+
+        if self.settled(&id).is_none() && self.replay.is_some() {
+            self.interrupt = Some(Interrupt::Await(id));
+            return Value::None;
+        }
+
+        while self.settled(&id).is_none() {
+            if self.failed() {
+                return Value::None;
+            }
+
+            if !self.poll_executor() {
+                self.report(
+                    "deadlock",
+                    "Awaited future can never be resolved",
+                    word_expr.node.start,
+                    word_expr.node.end,
+                );
+                return Value::None;
+            }
+        }
+
+        match self.settled(&id).unwrap() {
+            Ok(value) => value,
+            Err(value) => {
+                self.executor.unhandled.retain(|(task, _)| *task != id);
+                self.throw(value)
+            }
+        }
+    }
+
+    /// Value of a resolved future or the value thrown by a failed task
+    fn settled(&self, id: &str) -> Option<Result<Value, Value>> {
+        match self.executor.futures.get(id) {
+            Some((_, FutureState::Resolved(value))) => Some(Ok(value.clone())),
+            Some((_, FutureState::Failed(value))) => Some(Err(value.clone())),
+            _ => None,
+        }
+    }
+
+    /// Runs all scheduled tasks and timers, a task that failed and was never awaited is an uncaught error
+    pub(super) fn run_executor(&mut self) {
+        while !self.failed() && self.poll_executor() {}
+
+        if self.failed() || self.executor.unhandled.is_empty() {
+            return;
+        }
+
+        let (id, throw_site) = self.executor.unhandled.remove(0);
+        let Some(Err(value)) = self.settled(&id) else {
+            unreachable!("Only failed tasks are unhandled");
+        };
+
+        self.throw_site = throw_site;
+        let error = self.uncaught(value);
+        self.fail(error);
+    }
+
+    /// Runs the next ready task or waits for the closest timer, false if there is nothing to do
+    fn poll_executor(&mut self) -> bool {
+        if let Some(id) = self.executor.ready.pop_front() {
+            self.run_task(&id);
+            return true;
+        }
+
+        let timer = self
+            .executor
+            .futures
+            .iter()
+            .filter_map(|(id, (_, future))| match future {
+                FutureState::Sleep(deadline) => Some((*deadline, id.clone())),
+                _ => None,
+            })
+            .min();
+
+        match timer {
+            Some((deadline, id)) => {
+                thread::sleep(deadline.saturating_duration_since(Instant::now()));
+                self.resolve(&id, Value::None);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn resolve(&mut self, id: &str, value: Value) {
+        self.settle(id, FutureState::Resolved(value));
+    }
+
+    fn settle(&mut self, id: &str, state: FutureState) {
+        self.executor.futures.get_mut(id).unwrap().1 = state;
+
+        if let Some(tasks) = self.executor.waiting.remove(id) {
+            self.executor.ready.extend(tasks);
+        }
+    }
+
+    /// Evaluates the task until it finishes or awaits a pending future
+    fn run_task(&mut self, id: &str) {
+        let future = &mut self.executor.futures.get_mut(id).unwrap().1;
+        let mut task = match std::mem::replace(future, FutureState::Running) {
+            FutureState::Task(task) => task,
+            _ => unreachable!("Only tasks are scheduled"),
+        };
+
+        // The task may be run while another one awaits outside of its statements
+        let replay = self.replay.replace(Replay::default());
+        let control = self.resume_body(&mut task);
+        self.replay = replay;
+
+        match control {
+            ControlFlow::Await(future) => {
+                self.executor.futures.get_mut(id).unwrap().1 = FutureState::Task(task);
+                self.executor
+                    .waiting
+                    .entry(future)
+                    .or_default()
+                    .push(id.to_owned());
+            }
+            ControlFlow::Return(value) => self.resolve(id, value),
+            ControlFlow::Throw(value) => {
+                self.executor
+                    .unhandled
+                    .push((id.to_owned(), self.throw_site.clone()));
+                self.settle(id, FutureState::Failed(value));
+            }
+            _ => self.resolve(id, Value::None),
+        }
+    }
+
+    /// Drops the settled futures and timers the program doesn't hold and no task awaits, tasks still run
+    pub(super) fn collect_futures(&mut self) {
+        let executor = &mut self.executor;
+
+        executor.futures.retain(|id, (refs, future)| {
+            let unused = refs.strong_count() == 0 && !executor.waiting.contains_key(id);
+
+            match future {
+                FutureState::Resolved(_) | FutureState::Sleep(_) => !unused,
+                // Failures that are never awaited are reported once the executor drains
+                FutureState::Failed(_) => {
+                    !unused || executor.unhandled.iter().any(|(task, _)| task == id)
+                }
+                FutureState::Task(_) | FutureState::Running => true,
+            }
+        });
+    }
+
+    /// Starts a statement of a task, a statement suspended by an `await` gets the results of its calls back
+    pub(super) fn start_statement(&mut self) {
+        if self.replay.is_none() {
+            return;
+        }
+
+        let values = match self.resume_path.last() {
+            Some(ResumePoint::Replay(_)) => match self.resume_point() {
+                Some(ResumePoint::Replay(values)) => values,
+                _ => unreachable!(),
+            },
+            _ => vec![],
+        };
+
+        self.replay = Some(Replay { values, pos: 0 });
+    }
+
+    /// Stops the statement of a task that awaits a pending future, it is evaluated again once the task is resumed
+    pub(super) fn suspend_statement(&mut self, index: usize, future: String) -> ControlFlow {
+        let values = self
+            .replay
+            .as_mut()
+            .map(|replay| std::mem::take(&mut replay.values));
+
+        self.resume_path
+            .push(ResumePoint::Replay(values.unwrap_or_default()));
+        self.resume_path.push(ResumePoint::Block(index));
+
+        ControlFlow::Await(future)
+    }
+
+    /// Result of a call made before the statement was suspended
+    pub(super) fn replayed(&mut self) -> Option<Value> {
+        let replay = self.replay.as_mut()?;
+        let value = replay.values.get(replay.pos)?.clone();
+
+        replay.pos += 1;
+
+        Some(value)
+    }
+
+    pub(super) fn record(&mut self, value: &Value) {
+        if let Some(replay) = self.replay.as_mut() {
+            replay.values.push(value.clone());
+            replay.pos += 1;
+        }
+    }
+}
@@ -0,0 +1,187 @@
+use symboscript_types::{interpreter::*, parser::*};
+
+use super::Interpreter;
+
+/// Suspended function body, shared by generators and async tasks
+pub struct Suspended {
+    body: BlockStatement,
+
+    /// Scopes the function is declared in
+    env: Vec<Handle>,
+
+    /// Index of the source file the function is declared in
+    file: usize,
+
+    /// Scopes of the body from the function scope to the innermost one, they stay in the vault
+    scopes: Vec<String>,
+
+    /// Where the body stopped, innermost first
+    resume_path: Vec<ResumePoint>,
+}
+
+/// Position inside a statement a suspended generator continues from
+pub enum ResumePoint {
+    /// Index of the statement in a block
+    Block(usize),
+
+    /// Taken branch of an `if` statement
+    If(bool),
+
+    /// Body of a `while`, `for` or `loop` statement
+    Loop,
+
+    /// Body of a `for-in` loop with the items left
+    ForIn(ForInItems),
+
+    /// Section of a `try` statement with the completion of the sections before it
+    Try(TrySection, Box<ControlFlow>),
+
+    /// Results of the calls made by a statement suspended by an `await`, the statement is evaluated again
+    Replay(Vec<Value>),
+}
+
+#[derive(Clone, Copy)]
+pub enum TrySection {
+    Block,
+    Handler,
+    Finalizer,
+}
+
+/// Items a `for-in` loop walks over
+pub enum ForInItems {
+    Values(Box<dyn Iterator<Item = Value>>),
+    Generator(Handle),
+}
+
+impl Interpreter {
+    /// Keeps the function scope with the arguments until the body is resumed
+    pub(super) fn suspend_call(&mut self, closure: &Closure) -> Suspended {
+        let scopes = self.take_scopes(self.scope_stack.len() - 1);
+
+        Suspended {
+            body: closure.declarator.body.clone(),
+            env: closure.env.clone(),
+            file: closure.file,
+            scopes,
+            resume_path: vec![],
+        }
+    }
+
+    /// Evaluates the body from where it stopped, its scopes are kept if it is suspended again
+    pub(super) fn resume_body(&mut self, suspended: &mut Suspended) -> ControlFlow {
+        let caller_stack = self.enter_env(&suspended.env);
+        let caller_file = std::mem::replace(&mut self.file, suspended.file);
+        let base = self.scope_stack.len();
+
+        self.scope_stack.append(&mut suspended.scopes);
+        self.update_current_scope();
+
+        self.resume_path = std::mem::take(&mut suspended.resume_path);
+
+        let control = self.eval_block(&suspended.body);
+
+        if control.is_suspension() {
+            suspended.scopes = self.take_scopes(base);
+            suspended.resume_path = std::mem::take(&mut self.resume_path);
+        } else {
+            while self.scope_stack.len() > base {
+                self.decrement_scope();
+            }
+        }
+
+        self.exit_env(caller_stack);
+        self.file = caller_file;
+
+        control
+    }
+
+    pub(super) fn new_generator(&mut self, closure: &Closure) -> Value {
+        let generator = self.suspend_call(closure);
+
+        let handle = Handle::new(self.gen_id());
+        self.generators
+            .insert(handle.id.clone(), (handle.downgrade(), generator));
+        self.maybe_collect();
+
+        Value::Generator(handle)
+    }
+
+    /// Runs the generator until the next `yield`, `None` if it is finished
+    pub(super) fn resume_generator(&mut self, id: &str) -> Option<Value> {
+        // A running generator is not in `generators`, so it can't be resumed from its own body
+        let (refs, mut generator) = self.generators.remove(id)?;
+
+        // Generator bodies are not part of a task, so an `await` in them blocks
+        let replay = self.replay.take();
+        let control = self.resume_body(&mut generator);
+        self.replay = replay;
+
+        match control {
+            ControlFlow::Yield(value) => {
+                self.generators.insert(id.to_owned(), (refs, generator));
+
+                Some(value)
+            }
+            ControlFlow::Throw(val) => {
+                self.throw(val);
+                None
+            }
+            _ => None,
+        }
+    }
+
+    /// Pops scopes down to `base` without deleting them
+    fn take_scopes(&mut self, base: usize) -> Vec<String> {
+        let scopes = self.scope_stack.split_off(base);
+        self.update_current_scope();
+
+        scopes
+    }
+
+    /// Pops the resume point of the statement being resumed
+    pub(super) fn resume_point(&mut self) -> Option<ResumePoint> {
+        self.resume_path.pop()
+    }
+
+    /// Whether the statement being evaluated is resumed, so its scopes are already restored
+    pub(super) fn is_resuming(&self) -> bool {
+        !self.resume_path.is_empty()
+    }
+
+    /// Resumes the loop or starts it in a new scope, true if resumed
+    pub(super) fn resume_loop(&mut self) -> bool {
+        match self.resume_point() {
+            Some(ResumePoint::Loop) => true,
+            None => {
+                self.increment_scope();
+                false
+            }
+            Some(_) => unreachable!("Resume path doesn't match the loop"),
+        }
+    }
+
+    pub(super) fn next_item(&mut self, items: &mut ForInItems) -> Option<Value> {
+        match items {
+            ForInItems::Values(values) => values.next(),
+            ForInItems::Generator(generator) => self.resume_generator(&generator.id),
+        }
+    }
+
+    /// Drops the generators the program doesn't hold anymore with the scopes of their bodies
+    pub(super) fn collect_generators(&mut self) {
+        let abandoned = self
+            .generators
+            .iter()
+            .filter(|(_, (refs, _))| refs.strong_count() == 0)
+            .map(|(id, _)| id.clone())
+            .collect::<Vec<_>>();
+
+        for id in abandoned {
+            let (_, generator) = self.generators.remove(&id).unwrap();
+
+            for scope in generator.scopes.iter().rev() {
+                self.release_scope(scope);
+            }
+        }
+    }
+}
@@ -1,14 +1,24 @@
 #[macro_export]
 macro_rules! loop_controls {
-    ($self:ident, $block: expr) => {
+    ($self:ident, $block: expr, $resume_point: expr) => {
         let control = $self.eval_block(&$block);
-        match control {
+        $crate::loop_controls!(@control $self, control, $resume_point);
+    };
+
+    (@control $self:ident, $control: ident, $resume_point: expr) => {
+        match $control {
             ControlFlow::Break => break,
             ControlFlow::None(_) | ControlFlow::Continue => {}
 
+            // Scopes of a suspended body are kept until it is resumed
+            ControlFlow::Yield(_) | ControlFlow::Await(_) => {
+                $self.resume_path.push($resume_point);
+                return $control;
+            }
+
             _ => {
                 $self.decrement_scope();
-                return control;
+                return $control;
             }
         }
     };
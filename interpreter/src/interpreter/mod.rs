@@ -1,28 +1,48 @@
-use std::{collections::HashMap, fs, path::Path};
-
+use std::{
+    collections::HashMap,
+    fs,
+    path::Path,
+    rc::{Rc, Weak},
+};
+
+use num_complex::Complex64;
+use num_traits::ToPrimitive;
 use rand::{distributions::Alphanumeric, Rng};
 use symboscript_types::{interpreter::*, lexer::*, parser::*};
-use symboscript_utils::report_error;
 
 use colored::Colorize;
 
+mod collector;
+mod executor;
+mod generator;
 mod macro_utils;
 mod native;
 
-use crate::loop_controls;
+use crate::{expect_args, loop_controls};
 use symboscript_parser as parser;
+use symboscript_utils::render::LineIndex;
 
-use self::native::{get_values, StdLang};
+use self::{
+    executor::{Executor, Replay},
+    generator::{ForInItems, ResumePoint, Suspended, TrySection},
+    native::{get_values, StdLang},
+};
 
 pub struct Interpreter {
-    /// Path of the source file
+    /// Paths of the program and of the imported modules
     paths: Vec<String>,
 
-    /// Sources of programs
-    sources: Vec<String>,
+    /// Sources of the program and of the imported modules
+    sources: Vec<Rc<str>>,
+
+    /// Index of the file being evaluated in `paths` and `sources`
+    file: usize,
 
     scope_stack: Vec<String>,
 
+    /// Number of the last scope, names of scopes are not reused since closures keep them
+    scope_count: usize,
+
     current_scope: String,
 
     vault: Vault,
@@ -30,6 +50,56 @@ pub struct Interpreter {
     std_lang: StdLang,
 
     repl: bool,
+
+    /// Suspended generators by id, with a reference that tells whether the program still holds them
+    generators: HashMap<String, (Weak<()>, Suspended)>,
+
+    /// Scopes that were left but are seen by closures, they are freed once the closures are gone
+    detached: Vec<String>,
+
+    /// Number of kept scopes, generators and futures that starts the next collection
+    collect_at: usize,
+
+    /// Path to the `yield` or `await` a body is resumed from, innermost first
+    resume_path: Vec<ResumePoint>,
+
+    executor: Executor,
+
+    /// Call results of the current statement, only set while a task is evaluated
+    replay: Option<Replay>,
+
+    /// Stops the evaluation of the current statement
+    interrupt: Option<Interrupt>,
+
+    /// Functions being called, innermost last
+    call_stack: Vec<CallFrame>,
+
+    /// Site of the last `throw`
+    throw_site: Option<ThrowSite>,
+
+    /// Formula variables being evaluated with their scopes and declarations, innermost last
+    formula_stack: Vec<(String, String, Node)>,
+
+    /// Unknown variables and functions stay symbolic, set while the expression of a formula is evaluated
+    symbolic: bool,
+
+    /// Set by native methods that change their receiver, like `push`, so it is written back
+    receiver_changed: bool,
+}
+
+/// Statement of a `throw`, its file and the call stack at it
+type ThrowSite = (Node, usize, Vec<CallFrame>);
+
+/// Reason the current statement can't be evaluated further
+enum Interrupt {
+    /// Pending future awaited by a task
+    Await(String),
+
+    /// Value thrown by a called function
+    Throw(Value),
+
+    /// Runtime error, it is kept until the program is left
+    Error(Box<RuntimeError>),
 }
 
 fn get_full_path(path: &str) -> String {
@@ -45,28 +115,131 @@ impl Interpreter {
 
         Self {
             paths: vec![get_full_path(path)],
-            sources: vec![source.to_owned()],
+            sources: vec![source.into()],
+            file: 0,
             scope_stack: vec![],
+            scope_count: 0,
             current_scope: String::new(),
             vault,
             std_lang: get_values(),
             repl: print_expr,
+            generators: HashMap::new(),
+            detached: vec![],
+            collect_at: 0,
+            resume_path: vec![],
+            executor: Executor::default(),
+            replay: None,
+            interrupt: None,
+            call_stack: vec![],
+            throw_site: None,
+            formula_stack: vec![],
+            symbolic: false,
+            receiver_changed: false,
         }
     }
 
-    pub fn run(&mut self, ast: Ast) {
+    pub fn run(&mut self, ast: Ast) -> Result<(), Box<RuntimeError>> {
         self.initialize();
 
-        self.eval_ast(ast);
+        if let ControlFlow::Throw(value) = self.eval_ast(ast)? {
+            return Err(Box::new(self.uncaught(value)));
+        }
+
+        self.run_executor();
+
+        match self.interrupt.take() {
+            Some(Interrupt::Error(error)) => Err(error),
+            _ => Ok(()),
+        }
     }
 
-    pub fn eval_ast(&mut self, ast: Ast) -> ControlFlow {
-        self.eval_block(&ast.program.body)
+    /// Error for a value thrown up to the top level with the call stack of the `throw`
+    fn uncaught(&self, value: Value) -> RuntimeError {
+        let (node, file, frames) = match &value {
+            Value::Err(err) if err.span.is_some() => {
+                (err.span.unwrap(), err.file, err.stack.clone())
+            }
+            _ => self.throw_site.clone().unwrap_or_default(),
+        };
+
+        RuntimeError {
+            code: "uncaught-exception".to_owned(),
+            message: format!("Uncaught exception: {value}"),
+            node,
+            path: self.paths[file].clone(),
+            source: self.sources[file].clone(),
+            traceback: self.traceback(&frames, node, file),
+            labels: vec![],
+            notes: vec![],
+            related: vec![],
+        }
+    }
+
+    /// Lines of the calls down to the node in the file, outermost first
+    fn traceback(&self, frames: &[CallFrame], node: Node, file: usize) -> Vec<TraceFrame> {
+        // Only the files on the stack are indexed
+        let mut indexes: HashMap<usize, LineIndex> = HashMap::new();
+        let mut frame = |function: &str, file: usize, node: Node| {
+            indexes
+                .entry(file)
+                .or_insert_with(|| LineIndex::new(&self.sources[file]))
+                .trace_frame(function, &self.paths[file], node.start)
+        };
+
+        let mut function = "<main>";
+        let mut traceback = vec![];
+
+        for call in frames {
+            traceback.push(frame(function, call.file, call.node));
+            function = &call.name;
+        }
+
+        traceback.push(frame(function, file, node));
+
+        traceback
+    }
+
+    pub fn eval_ast(&mut self, ast: Ast) -> Result<ControlFlow, Box<RuntimeError>> {
+        let control = self.eval_block(&ast.program.body);
+
+        match self.interrupt.take() {
+            Some(Interrupt::Error(error)) => Err(error),
+            interrupt => {
+                self.interrupt = interrupt;
+                Ok(control)
+            }
+        }
     }
 
     fn eval_block(&mut self, body: &BlockStatement) -> ControlFlow {
-        for statement in body {
-            let control = self.eval_statement(&statement);
+        let start = match self.resume_point() {
+            // The `yield` itself is not evaluated again
+            Some(ResumePoint::Block(i)) if !self.is_resuming() => i + 1,
+            Some(ResumePoint::Block(i)) => i,
+            Some(_) => unreachable!("Resume path doesn't match the block"),
+            None => 0,
+        };
+
+        for (i, statement) in body.iter().enumerate().skip(start) {
+            // Statements after a pending `await` or a throw in a call are not evaluated
+            if self.interrupt.is_some() {
+                break;
+            }
+
+            self.start_statement();
+
+            let control = self.eval_statement(statement);
+
+            // Errors stay pending, so every block they pass through is left
+            if self.failed() {
+                return ControlFlow::Error;
+            }
+
+            match self.interrupt.take() {
+                Some(Interrupt::Await(future)) => return self.suspend_statement(i, future),
+                Some(Interrupt::Throw(value)) => return ControlFlow::Throw(value),
+                Some(Interrupt::Error(_)) | None => {}
+            }
 
             match control {
                 ControlFlow::None(v) => {
@@ -74,24 +247,40 @@ impl Interpreter {
                         println!("{}", format!("> {} <", v).green());
                     }
                 }
+                _ if control.is_suspension() => {
+                    self.resume_path.push(ResumePoint::Block(i));
+                    return control;
+                }
                 _ => return control,
             }
         }
 
-        return ControlFlow::None(Value::None);
+        ControlFlow::None(Value::None)
     }
 
     fn eval_statement(&mut self, statement: &Statement) -> ControlFlow {
         match statement {
             Statement::ExpressionStatement(expr) => {
-                return ControlFlow::None(self.eval_expression(&expr));
+                return ControlFlow::None(self.eval_expression(expr));
             }
 
             Statement::ReturnStatement(v) => {
                 return ControlFlow::Return(self.eval_expression(&v.argument));
             }
             Statement::ThrowStatement(v) => {
-                return ControlFlow::Throw(self.eval_expression(&v.argument));
+                let mut value = self.eval_expression(&v.argument);
+                self.throw_site = Some((v.node, self.file, self.call_stack.clone()));
+
+                // Errors keep where they were thrown first, so `throw e` in `catch` doesn't move them
+                if let Value::Err(err) = &mut value {
+                    if err.span.is_none() {
+                        err.span = Some(v.node);
+                        err.file = self.file;
+                        err.stack = self.call_stack.clone();
+                    }
+                }
+
+                return ControlFlow::Throw(value);
             }
             Statement::ContinueStatement(_) => {
                 return ControlFlow::Continue;
@@ -99,45 +288,67 @@ impl Interpreter {
             Statement::BreakStatement(_) => {
                 return ControlFlow::Break;
             }
-            Statement::YieldStatement(_) => todo!(),
+            Statement::YieldStatement(v) => {
+                return ControlFlow::Yield(self.eval_expression(&v.argument));
+            }
             Statement::VariableDeclaration(decl) => {
-                let value = if decl.is_formula {
-                    Value::Ast(decl.init.clone())
-                } else {
-                    self.eval_expression(&decl.init)
-                };
+                match &decl.id {
+                    Pattern::Identifier(id) if decl.is_formula => {
+                        self.declare_variable(&id.name, Value::Ast(decl.init.clone()));
+                        self.vault
+                            .get_mut(&self.current_scope)
+                            .unwrap()
+                            .formulas
+                            .insert(id.name.clone(), decl.node);
+                    }
+                    _ => {
+                        let value = self.eval_expression(&decl.init);
 
-                self.declare_variable(&decl.id, value);
+                        // The statement is left by a throw before the value is unpacked
+                        if self.interrupt.is_none() {
+                            self.bind_pattern(&decl.id, value, true);
+                        }
+                    }
+                }
             }
             Statement::FunctionDeclaration(decl) => {
-                self.declare_variable(&decl.id, Value::Function(decl.clone()));
+                let closure = self.new_closure(decl);
+                self.declare_variable(&decl.id, closure);
             }
             Statement::ScopeDeclaration(decl) => {
                 let scope = self.start_declaration_of_named_scope(&decl.id);
 
-                self.eval_block(&decl.body);
+                let control = self.eval_block(&decl.body);
+                self.check_no_yield(control, decl.node);
                 self.end_declaration_of_named_scope(&scope);
             }
             Statement::ContextDeclaration(decl) => {
                 let scope = self.start_declaration_of_named_scope(&decl.id);
-                self.declare_variable(&"this".to_owned(), Value::ScopeRef(scope.clone()));
-                self.eval_block(&decl.body);
+                self.declare_variable("this", Value::ScopeRef(scope.clone()));
+                let control = self.eval_block(&decl.body);
+                self.check_no_yield(control, decl.node);
                 self.end_declaration_of_named_scope(&scope);
             }
             Statement::IfStatement(if_stmt) => {
                 return self.eval_if_statement(if_stmt);
             }
-            Statement::ForStatement(_) => todo!(),
+            Statement::TryStatement(try_stmt) => {
+                return self.eval_try_statement(try_stmt);
+            }
+            Statement::ForStatement(for_stmt) => {
+                return self.eval_for_statement(for_stmt);
+            }
+            Statement::ForInStatement(for_in_stmt) => {
+                return self.eval_for_in_statement(for_in_stmt);
+            }
             Statement::WhileStatement(while_stmt) => {
-                self.eval_while_statement(while_stmt);
+                return self.eval_while_statement(while_stmt);
             }
             Statement::LoopStatement(loop_stmt) => {
-                self.eval_loop_statement(loop_stmt);
+                return self.eval_loop_statement(loop_stmt);
             }
             Statement::BlockStatement(body) => {
-                self.increment_scope();
-                self.eval_block(body);
-                self.decrement_scope();
+                return self.eval_scoped_block(body, None);
             }
 
             Statement::AssignStatement(assign_stmt) => {
@@ -149,16 +360,67 @@ impl Interpreter {
             }
         }
 
-        return ControlFlow::None(Value::None);
+        ControlFlow::None(Value::None)
     }
 
-    pub fn push_file(&mut self, path: String, source: String) {
-        self.sources.push(source);
+    /// Scope declarations can't be suspended, so a `yield` or a pending `await` in their body is reported
+    fn check_no_yield(&mut self, control: ControlFlow, node: Node) {
+        if control.is_suspension() {
+            self.report(
+                "unsupported-suspension",
+                "`yield` and `await` can't suspend scope declarations",
+                node.start,
+                node.end,
+            );
+        }
+    }
+
+    /// Evaluates the block in a new scope with an optional variable declared in it
+    fn eval_scoped_block(
+        &mut self,
+        body: &BlockStatement,
+        variable: Option<(&str, Value)>,
+    ) -> ControlFlow {
+        if !self.is_resuming() {
+            self.increment_scope();
+
+            if let Some((name, value)) = variable {
+                self.declare_variable(name, value);
+            }
+        }
+
+        let control = self.eval_block(body);
+
+        // Scopes of a suspended body are kept until it is resumed
+        if !control.is_suspension() {
+            self.decrement_scope();
+        }
+
+        control
+    }
+
+    /// Loop conditions are evaluated again on every iteration, so they can't be suspended
+    fn check_not_suspended(&mut self, node: Node) {
+        if let Some(Interrupt::Await(_)) = self.interrupt {
+            self.report(
+                "unsupported-suspension",
+                "`await` can't suspend a loop condition, await the value before the loop",
+                node.start,
+                node.end,
+            );
+        }
+    }
+
+    /// Adds a file and returns its index
+    pub fn push_file(&mut self, path: String, source: String) -> usize {
+        self.sources.push(source.into());
         self.paths.push(path);
+
+        self.paths.len() - 1
     }
 
     pub fn append_to_current_source(&mut self, source: String) {
-        self.sources.last_mut().unwrap().push_str(&source);
+        self.sources[self.file] = format!("{}{source}", self.sources[self.file]).into();
     }
 
     fn eval_import_statement(&mut self, import_stmt: &ImportStatement) {
@@ -168,7 +430,7 @@ impl Interpreter {
             format!("{}.syms", import_stmt.source.name.clone())
         };
 
-        let current_path = Path::new(self.paths.last().unwrap());
+        let current_path = Path::new(&self.paths[self.file]);
 
         let current_path = match current_path.parent() {
             Some(path) => path,
@@ -182,36 +444,59 @@ impl Interpreter {
 
         match file_contents {
             Ok(contents) => {
-                let ast = parser::Parser::new(&file_path, &contents).parse();
-
-                {
-                    self.push_file(file_path.clone(), contents.clone());
-
-                    {
+                let parsed = parser::Parser::new(&file_path, &contents).parse();
+
+                // The module is evaluated like a call, so errors in it show the import in their traceback
+                let file = self.push_file(file_path.clone(), contents);
+                self.call_stack.push(CallFrame {
+                    name: format!("<module {}>", import_stmt.as_name.name),
+                    node: import_stmt.node,
+                    file: self.file,
+                });
+                let caller_file = std::mem::replace(&mut self.file, file);
+
+                match parsed {
+                    Ok(ast) => {
                         let scope =
                             self.start_declaration_of_named_scope(&import_stmt.as_name.name);
 
                         // Declare standard variables
+                        self.declare_variable("__file__", Value::Str(file_path.clone()));
                         self.declare_variable(
-                            &"__file__".to_owned(),
-                            Value::Str(file_path.clone()),
-                        );
-                        self.declare_variable(
-                            &"__name__".to_owned(),
+                            "__name__",
                             Value::Str(import_stmt.as_name.name.clone()),
                         );
-                        self.declare_variable(&"__module__".to_owned(), Value::Bool(true));
+                        self.declare_variable("__module__", Value::Bool(true));
 
-                        // Evaluate the AST
-                        self.eval_ast(ast);
+                        // Evaluate the AST, an error in it stays pending
+                        self.eval_block(&ast.program.body);
                         self.end_declaration_of_named_scope(&scope);
                     }
-
-                    self.paths.pop();
-                    self.sources.pop();
+                    // The errors point into the module, where they can be fixed
+                    Err(errors) => {
+                        let mut errors = errors.iter().map(|error| {
+                            self.error(
+                                "import-failed",
+                                &format!(
+                                    "Failed to import module `{}`: {}",
+                                    import_stmt.source, error.message
+                                ),
+                                error.node,
+                            )
+                        });
+
+                        if let Some(mut error) = errors.next() {
+                            error.related = errors.collect();
+                            self.fail(error);
+                        }
+                    }
                 }
+
+                self.file = caller_file;
+                self.call_stack.pop();
             }
             Err(e) => self.report(
+                "import-failed",
                 &format!("Failed to import module: `{}`\n{e}", import_stmt.source),
                 import_stmt.node.start,
                 import_stmt.node.end,
@@ -222,7 +507,40 @@ impl Interpreter {
     fn eval_assign_statement(&mut self, assign_stmt: &AssignStatement) -> ControlFlow {
         let right = self.eval_expression(&assign_stmt.right);
 
-        let var_val = self.get_variable_value_mut(&assign_stmt.left);
+        if self.interrupt.is_some() {
+            return ControlFlow::None(Value::None);
+        }
+
+        let left = match &assign_stmt.left {
+            Pattern::Identifier(id) => id,
+            pattern => {
+                self.bind_pattern(pattern, right, false);
+                return ControlFlow::None(Value::None);
+            }
+        };
+
+        let indices = assign_stmt
+            .indices
+            .iter()
+            .map(|index| self.eval_expression(index))
+            .collect::<Vec<Value>>();
+
+        let Some(var_val) = self.get_variable_value_mut(left) else {
+            return ControlFlow::None(Value::None);
+        };
+
+        let var_val = match Self::index_mut(var_val, &indices) {
+            Ok(var_val) => var_val,
+            Err(e) => {
+                self.report(
+                    "index-error",
+                    &e,
+                    assign_stmt.node.start,
+                    assign_stmt.node.end,
+                );
+                return ControlFlow::None(Value::None);
+            }
+        };
 
         match assign_stmt.operator {
             AssignOperator::Assign => {
@@ -251,47 +569,488 @@ impl Interpreter {
         ControlFlow::None(Value::None)
     }
 
+    /// Declares (`let`) or assigns (`mut`) the variables of the pattern to the parts of the value
+    fn bind_pattern(&mut self, pattern: &Pattern, value: Value, declare: bool) {
+        match pattern {
+            Pattern::Identifier(id) if declare => self.declare_variable(&id.name, value),
+            Pattern::Identifier(id) => {
+                if let Some(var_val) = self.get_variable_value_mut(id) {
+                    *var_val = value;
+                }
+            }
+            Pattern::Wildcard(_) => {}
+            Pattern::Literal(_) | Pattern::Range(_) => {
+                unreachable!("Literal and range patterns are only parsed in `match`")
+            }
+            Pattern::Sequence(seq_pattern) => {
+                let items = match value {
+                    Value::Sequence(items) => items,
+                    got => {
+                        self.report(
+                            "destructuring-mismatch",
+                            &format!("Expected a sequence to destructure, got `{got}`"),
+                            seq_pattern.node.start,
+                            seq_pattern.node.end,
+                        );
+                        return;
+                    }
+                };
+
+                let len = seq_pattern.elements.len();
+
+                let fits = match seq_pattern.rest {
+                    Some(_) => items.len() >= len,
+                    None => items.len() == len,
+                };
+
+                if !fits {
+                    self.report(
+                        "destructuring-mismatch",
+                        &format!(
+                            "Expected {}{len} items to destructure, got {}",
+                            if seq_pattern.rest.is_some() {
+                                "at least "
+                            } else {
+                                ""
+                            },
+                            items.len()
+                        ),
+                        seq_pattern.node.start,
+                        seq_pattern.node.end,
+                    );
+                    return;
+                }
+
+                let mut items = items.into_iter();
+
+                for element in &seq_pattern.elements {
+                    self.bind_pattern(element, items.next().unwrap(), declare);
+                }
+
+                if let Some(rest) = &seq_pattern.rest {
+                    let rest = Pattern::Identifier(rest.clone());
+                    self.bind_pattern(&rest, Value::Sequence(items.collect()), declare);
+                }
+            }
+            Pattern::Hashmap(map_pattern) => {
+                let scope = match value {
+                    Value::ScopeRef(scope) => scope,
+                    got => {
+                        self.report(
+                            "destructuring-mismatch",
+                            &format!("Expected a hashmap to destructure, got `{got}`"),
+                            map_pattern.node.start,
+                            map_pattern.node.end,
+                        );
+                        return;
+                    }
+                };
+
+                for (key, pattern) in &map_pattern.entries {
+                    match self.vault[&scope].values.get(&key.name).cloned() {
+                        Some(value) => self.bind_pattern(pattern, value, declare),
+                        None => {
+                            self.report(
+                                "destructuring-mismatch",
+                                &format!("Key `{key}` not found in the destructured hashmap"),
+                                key.node.start,
+                                key.node.end,
+                            );
+                            return;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Checks the shape of the value against the pattern and collects the values of its variables
+    fn match_pattern(
+        &mut self,
+        pattern: &Pattern,
+        value: &Value,
+        bindings: &mut Vec<(String, Value)>,
+    ) -> bool {
+        match (pattern, value) {
+            (Pattern::Identifier(id), value) => {
+                bindings.push((id.name.clone(), value.clone()));
+                true
+            }
+            (Pattern::Wildcard(_), _) => true,
+            (Pattern::Literal(literal), value) => {
+                self.match_literal(literal).equal(value).as_bool()
+            }
+            (Pattern::Range(range), value) if value.is_number() => {
+                (range.start..=range.end).contains(&value.as_f64().unwrap())
+            }
+            (Pattern::Sequence(seq_pattern), Value::Sequence(items)) => {
+                let len = seq_pattern.elements.len();
+
+                let fits = match seq_pattern.rest {
+                    Some(_) => items.len() >= len,
+                    None => items.len() == len,
+                };
+
+                if !fits {
+                    return false;
+                }
+
+                for (element, item) in seq_pattern.elements.iter().zip(items) {
+                    if !self.match_pattern(element, item, bindings) {
+                        return false;
+                    }
+                }
+
+                if let Some(rest) = &seq_pattern.rest {
+                    bindings.push((rest.name.clone(), Value::Sequence(items[len..].to_vec())));
+                }
+
+                true
+            }
+            (Pattern::Hashmap(map_pattern), Value::ScopeRef(_) | Value::Err(_)) => {
+                for (key, pattern) in &map_pattern.entries {
+                    let value = match value {
+                        Value::ScopeRef(scope) => self.vault[scope].values.get(&key.name).cloned(),
+
+                        // Errors match by their fields, like `{kind: "ZeroDivision", message}`
+                        Value::Err(err) => match key.name.as_str() {
+                            "kind" => Some(Value::Str(err.kind.clone())),
+                            "message" => Some(Value::Str(err.message.clone())),
+                            "payload" => Some(err.payload.clone()),
+                            _ => None,
+                        },
+                        _ => unreachable!(),
+                    };
+
+                    let value = match value {
+                        Some(value) => value,
+                        None => return false,
+                    };
+
+                    if !self.match_pattern(pattern, &value, bindings) {
+                        return false;
+                    }
+                }
+
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Walks down nested sequences by `indices`
+    fn index_mut<'b>(value: &'b mut Value, indices: &[Value]) -> Result<&'b mut Value, String> {
+        let mut value = value;
+
+        for index in indices {
+            value = match value {
+                Value::Sequence(seq) => {
+                    let index = Self::seq_index(seq.len(), index)?;
+                    &mut seq[index]
+                }
+                got => return Err(format!("`{got}` is not a sequence")),
+            };
+        }
+
+        Ok(value)
+    }
+
+    /// Converts a (possibly negative) index into a position in a sequence of length `len`
+    fn seq_index(len: usize, index: &Value) -> Result<usize, String> {
+        let index = match index.to_integer().and_then(|i| i.to_isize()) {
+            Some(index) => index,
+            None => return Err(format!("Sequence index must be an integer, got `{index}`")),
+        };
+
+        // Ranges can be longer than `isize::MAX`
+        let position = if index < 0 {
+            len.checked_sub(index.unsigned_abs())
+        } else {
+            Some(index as usize)
+        };
+
+        match position {
+            Some(position) if position < len => Ok(position),
+            _ => Err(format!(
+                "Index {index} is out of range for sequence of length {len}"
+            )),
+        }
+    }
+
     fn eval_if_statement(&mut self, if_stmt: &IfStatement) -> ControlFlow {
-        if (self.eval_expression(&if_stmt.test)).as_bool() {
-            return self.eval_block(&if_stmt.consequent);
+        let branch = match self.resume_point() {
+            Some(ResumePoint::If(branch)) => branch,
+            Some(_) => unreachable!("Resume path doesn't match the if statement"),
+            None => (self.eval_expression(&if_stmt.test)).as_bool(),
+        };
+
+        let control = if branch {
+            self.eval_block(&if_stmt.consequent)
         } else {
-            return self.eval_block(&if_stmt.alternate);
+            self.eval_block(&if_stmt.alternate)
+        };
+
+        if control.is_suspension() {
+            self.resume_path.push(ResumePoint::If(branch));
+        }
+
+        control
+    }
+
+    fn eval_try_statement(&mut self, try_stmt: &TryStatement) -> ControlFlow {
+        // Completion of the sections before the current one
+        let (mut section, mut control) = match self.resume_point() {
+            Some(ResumePoint::Try(section, control)) => (section, *control),
+            Some(_) => unreachable!("Resume path doesn't match the try statement"),
+            None => (TrySection::Block, ControlFlow::None(Value::None)),
+        };
+
+        loop {
+            let (body, variable) = match section {
+                TrySection::Block => (&try_stmt.block, None),
+                TrySection::Handler => {
+                    let variable = match (&try_stmt.param, &control) {
+                        (Some(param), ControlFlow::Throw(value)) => {
+                            Some((param.name.as_str(), value.clone()))
+                        }
+                        _ => None,
+                    };
+
+                    (try_stmt.handler.as_ref().unwrap(), variable)
+                }
+                TrySection::Finalizer => (try_stmt.finalizer.as_ref().unwrap(), None),
+            };
+
+            let result = self.eval_scoped_block(body, variable);
+
+            if result.is_suspension() {
+                self.resume_path
+                    .push(ResumePoint::Try(section, Box::new(control)));
+                return result;
+            }
+
+            match (section, result) {
+                // `finally` only replaces the completion with its own `return`, `throw` or loop control
+                (TrySection::Finalizer, ControlFlow::None(_)) => {}
+                (_, result) => control = result,
+            }
+
+            section = match section {
+                TrySection::Block
+                    if try_stmt.handler.is_some() && matches!(control, ControlFlow::Throw(_)) =>
+                {
+                    TrySection::Handler
+                }
+                TrySection::Block | TrySection::Handler if try_stmt.finalizer.is_some() => {
+                    TrySection::Finalizer
+                }
+                _ => return control,
+            };
         }
     }
 
     fn eval_while_statement(&mut self, while_stmt: &WhileStatement) -> ControlFlow {
-        self.increment_scope();
+        // A resumed loop continues its body before checking the test
+        let mut resumed = self.resume_loop();
 
-        while (self.eval_expression(&while_stmt.test)).as_bool() {
-            loop_controls!(self, while_stmt.body);
+        while std::mem::take(&mut resumed) || self.eval_loop_test(&while_stmt.test, while_stmt.node)
+        {
+            loop_controls!(self, while_stmt.body, ResumePoint::Loop);
         }
 
         self.decrement_scope();
 
-        return ControlFlow::None(Value::None);
+        ControlFlow::None(Value::None)
+    }
+
+    fn eval_for_statement(&mut self, for_stmt: &ForStatement) -> ControlFlow {
+        // Scope of the loop variable, every iteration gets its own scope on top of it
+        let mut resumed = self.resume_loop();
+
+        if !resumed {
+            self.eval_statement(&for_stmt.init);
+        }
+
+        while std::mem::take(&mut resumed) || self.eval_loop_test(&for_stmt.test, for_stmt.node) {
+            let control = self.eval_for_iteration(&for_stmt.body);
+            loop_controls!(@control self, control, ResumePoint::Loop);
+
+            self.eval_expression(&for_stmt.update);
+            self.check_not_suspended(for_stmt.node);
+        }
+
+        self.decrement_scope();
+
+        ControlFlow::None(Value::None)
+    }
+
+    /// Evaluates the body in a scope with copies of the loop variables, so closures made in an
+    /// iteration keep its values, changes to the copies are written back before the update
+    fn eval_for_iteration(&mut self, body: &BlockStatement) -> ControlFlow {
+        // A resumed iteration already has its scope restored
+        if !self.is_resuming() {
+            let loop_scope = &self.vault[&self.current_scope];
+            let (values, formulas) = (loop_scope.values.clone(), loop_scope.formulas.clone());
+
+            self.increment_scope();
+
+            let scope = self.vault.get_mut(&self.current_scope).unwrap();
+            scope.values = values;
+            scope.formulas = formulas;
+        }
+
+        let control = self.eval_block(body);
+
+        // Scopes of a suspended body are kept until it is resumed
+        if !control.is_suspension() {
+            let loop_scope = &self.scope_stack[self.scope_stack.len() - 2];
+            let iteration = &self.vault[&self.current_scope].values;
+
+            let changed = self.vault[loop_scope]
+                .values
+                .keys()
+                .filter_map(|name| Some((name.clone(), iteration.get(name)?.clone())))
+                .collect::<Vec<_>>();
+
+            self.vault
+                .get_mut(loop_scope)
+                .unwrap()
+                .values
+                .extend(changed);
+
+            self.decrement_scope();
+        }
+
+        control
+    }
+
+    /// A throw in the condition ends the loop
+    fn eval_loop_test(&mut self, test: &Expression, node: Node) -> bool {
+        let test = self.eval_expression(test);
+        self.check_not_suspended(node);
+
+        self.interrupt.is_none() && test.as_bool()
+    }
+
+    fn eval_for_in_statement(&mut self, for_in_stmt: &ForInStatement) -> ControlFlow {
+        let (mut items, mut resumed) = match self.resume_point() {
+            Some(ResumePoint::ForIn(items)) => (items, true),
+            Some(_) => unreachable!("Resume path doesn't match the for-in loop"),
+            None => {
+                let right = self.eval_expression(&for_in_stmt.right);
+
+                if self.interrupt.is_some() {
+                    return ControlFlow::None(Value::None);
+                }
+
+                let items = match self.iter_values(right, for_in_stmt.is_of) {
+                    Some(items) => items,
+                    None => {
+                        self.report(
+                            "type-mismatch",
+                            &format!("`{}` is not iterable", for_in_stmt.right),
+                            for_in_stmt.node.start,
+                            for_in_stmt.node.end,
+                        );
+                        return ControlFlow::None(Value::None);
+                    }
+                };
+
+                self.increment_scope();
+
+                (items, false)
+            }
+        };
+
+        loop {
+            // Every item is declared in the scope of its iteration, so closures made in it keep it
+            let item = if std::mem::take(&mut resumed) {
+                None
+            } else {
+                match self.next_item(&mut items) {
+                    Some(item) => Some((for_in_stmt.left.name.as_str(), item)),
+                    None => break,
+                }
+            };
+
+            let control = self.eval_scoped_block(&for_in_stmt.body, item);
+            loop_controls!(@control self, control, ResumePoint::ForIn(items));
+        }
+
+        self.decrement_scope();
+
+        ControlFlow::None(Value::None)
+    }
+
+    /// Items a `for-in` (or `for-of` if `values` is set) loop walks over, ranges are not collected
+    fn iter_values(&mut self, value: Value, values: bool) -> Option<ForInItems> {
+        let items: Box<dyn Iterator<Item = Value>> = match value {
+            Value::Sequence(seq) => Box::new(seq.into_iter()),
+            Value::Range(range) => Box::new(range.iter().map(Value::integral)),
+            Value::Generator(generator) => return Some(ForInItems::Generator(generator)),
+            Value::ScopeRef(scope) => {
+                let scope = &self.vault.get(&scope).unwrap().values;
+
+                let items = native::hashmap::entries(scope)
+                    .map(|(k, v)| {
+                        if values {
+                            v.clone()
+                        } else {
+                            Value::Str(k.clone())
+                        }
+                    })
+                    .collect::<Vec<Value>>();
+
+                Box::new(items.into_iter())
+            }
+            _ => return None,
+        };
+
+        Some(ForInItems::Values(items))
     }
 
     fn eval_loop_statement(&mut self, loop_stmt: &LoopStatement) -> ControlFlow {
-        self.increment_scope();
+        self.resume_loop();
 
         loop {
-            loop_controls!(self, loop_stmt.body);
+            loop_controls!(self, loop_stmt.body, ResumePoint::Loop);
         }
 
         self.decrement_scope();
 
-        return ControlFlow::None(Value::None);
+        ControlFlow::None(Value::None)
     }
 
     fn eval_expression(&mut self, expression: &Expression) -> Value {
         match expression {
             Expression::BinaryExpression(binary_expr) => self.eval_binary_expression(binary_expr),
             Expression::UnaryExpression(unary_expr) => self.eval_unary_expression(unary_expr),
-            Expression::ConditionalExpression(_) => todo!(),
+            Expression::ConditionalExpression(conditional_expr) => {
+                self.eval_conditional_expression(conditional_expr)
+            }
             Expression::CallExpression(call_expr) => self.eval_call_expression(call_expr),
             Expression::MemberExpression(member_expr) => self.eval_member_expression(member_expr),
-            Expression::SequenceExpression(_) => todo!(),
-            Expression::WordExpression(_) => todo!(),
+            Expression::SequenceExpression(seq_expr) => Value::Sequence(
+                seq_expr
+                    .expressions
+                    .iter()
+                    .map(|expr| self.eval_expression(expr))
+                    .collect(),
+            ),
+            Expression::WordExpression(word_expr) => match &word_expr.operator {
+                WordOperator::Await => self.eval_await(word_expr),
+                operator => {
+                    self.report(
+                        "unsupported-operator",
+                        &format!("`{operator}` is not supported yet"),
+                        word_expr.node.start,
+                        word_expr.node.end,
+                    );
+                    Value::None
+                }
+            },
+            Expression::FunctionExpression(decl) => self.new_closure(decl),
+            Expression::MatchExpression(match_expr) => self.eval_match_expression(match_expr),
 
             Expression::Literal(val) => self.match_literal(val),
 
@@ -301,40 +1060,57 @@ impl Interpreter {
         }
     }
 
-    fn set_native_value(&mut self, name: &str, value: Value) {
+    /// Sets the value native methods of `&name` work on and returns the previous one
+    fn set_native_value(&mut self, name: &str, value: Value) -> Value {
         self.vault
             .get_mut(&format!("std$0.&{name}$0"))
             .unwrap()
             .values
-            .insert("$value".to_owned(), value);
+            .insert("$value".to_owned(), value)
+            .unwrap_or(Value::None)
     }
 
     fn eval_member_expression(&mut self, member_expr: &MemberExpression) -> Value {
         let object = self.eval_expression(&member_expr.object);
 
-        let object: Identifier = match object {
-            Value::ScopeRef(ref_name) => Identifier {
-                name: ref_name.clone(),
-                node: member_expr.node.clone(),
-            },
-            Value::Sequence(_) => self.native_id("sequence", object, member_expr.node.clone()),
-            Value::None => self.native_id("none", object, member_expr.node.clone()),
-            Value::Number(_) => self.native_id("number", object, member_expr.node.clone()),
-            Value::Bool(_) => self.native_id("bool", object, member_expr.node.clone()),
-            Value::Str(_) => self.native_id("str", object, member_expr.node.clone()),
-            Value::Ast(_) => self.native_id("ast", object, member_expr.node.clone()),
-            Value::Err(_) => self.native_id("err", object, member_expr.node.clone()),
+        let native_name = match object {
+            Value::ScopeRef(_) => None,
+            Value::Sequence(_) => Some("sequence"),
+            Value::Range(_) => Some("range"),
+            Value::Generator(_) => Some("generator"),
+            Value::Future(_) => Some("future"),
+            Value::None => Some("none"),
+            Value::Number(_) | Value::Integer(_) | Value::Rational(_) | Value::Complex(_) => {
+                Some("number")
+            }
+            Value::Bool(_) => Some("bool"),
+            Value::Str(_) => Some("str"),
+            Value::Ast(_) => Some("ast"),
+            Value::Err(_) => Some("err"),
             _ => {
                 self.report(
-                    &format!("is not a scope"),
+                    "type-mismatch",
+                    "is not a scope",
                     member_expr.node.start,
                     member_expr.node.end,
                 );
-                unreachable!("Report ends proccess");
+                return Value::None;
             }
         };
 
-        self.enter_named_scope(&object.name);
+        // Native scopes are shared, so the outer receiver is restored after nested member expressions
+        let (object, prev_native_value) = match (native_name, object) {
+            (None, Value::ScopeRef(ref_name)) => (ref_name, Value::None),
+            (Some(name), object) => {
+                let prev = self.set_native_value(name, object);
+                (format!("std$0.&{name}$0"), prev)
+            }
+            _ => unreachable!(),
+        };
+
+        self.enter_named_scope(&object);
+
+        let receiver_changed = std::mem::take(&mut self.receiver_changed);
 
         let property = match &member_expr.property {
             Expression::Identifier(id) => {
@@ -343,7 +1119,7 @@ impl Interpreter {
 
                     self.get_variable_value(&Identifier {
                         name: property.to_string(),
-                        node: member_expr.node.clone(),
+                        node: member_expr.node,
                     })
                 } else {
                     self.get_variable_value(id)
@@ -355,29 +1131,77 @@ impl Interpreter {
 
                 self.get_variable_value(&Identifier {
                     name: property.to_string(),
-                    node: member_expr.node.clone(),
+                    node: member_expr.node,
                 })
             }
         };
 
         self.exit_named_scope();
 
+        if let Some(name) = native_name {
+            let receiver = self.set_native_value(name, prev_native_value);
+
+            // Formulas are evaluated again when they are read, so they are not overwritten
+            if std::mem::replace(&mut self.receiver_changed, receiver_changed)
+                && !self.is_formula(&member_expr.object)
+            {
+                self.assign_to_expression(&member_expr.object, receiver);
+            }
+        }
+
         property
     }
 
-    fn native_id(&mut self, name: &str, value: Value, node: Node) -> Identifier {
-        self.set_native_value(name, value);
-        Identifier {
-            name: format!("std$0.&{name}$0").to_owned(),
-            node,
+    /// Assigns to a variable, a member of a scope or an item of a sequence, other expressions are ignored
+    fn assign_to_expression(&mut self, target: &Expression, value: Value) {
+        match target {
+            Expression::Identifier(id) => {
+                if let Some(var_val) = self.get_variable_value_mut(id) {
+                    *var_val = value;
+                }
+            }
+            Expression::MemberExpression(member_expr) if !member_expr.is_expr => {
+                if let Expression::Identifier(property) = &member_expr.property {
+                    if let Value::ScopeRef(scope) = self.eval_expression(&member_expr.object) {
+                        self.vault
+                            .get_mut(&scope)
+                            .unwrap()
+                            .values
+                            .insert(property.name.clone(), value);
+                    }
+                }
+            }
+            // An item of a sequence, `m[0].push[5]` writes the changed item back into `m`
+            Expression::CallExpression(call_expr) if !self.is_formula(&call_expr.callee) => {
+                let index = match &call_expr.arguments {
+                    Expression::SequenceExpression(args) if args.expressions.len() == 1 => {
+                        &args.expressions[0]
+                    }
+                    _ => return,
+                };
+
+                let mut sequence = self.eval_expression(&call_expr.callee);
+                if !matches!(sequence, Value::Sequence(_)) {
+                    return;
+                }
+
+                let index = self.eval_expression(index);
+                match Self::index_mut(&mut sequence, &[index]) {
+                    Ok(item) => *item = value,
+                    Err(e) => {
+                        self.report("index-error", &e, call_expr.node.start, call_expr.node.end);
+                        return;
+                    }
+                }
+
+                self.assign_to_expression(&call_expr.callee, sequence);
+            }
+            _ => {}
         }
     }
 
     fn eval_call_expression(&mut self, call_expr: &CallExpression) -> Value {
-        let var = self.get_variable_value(&Identifier {
-            name: call_expr.callee.clone(),
-            node: call_expr.node.clone(),
-        });
+        let var = self.eval_expression(&call_expr.callee);
 
         let args = match &call_expr.arguments {
             Expression::SequenceExpression(seq_exp) => seq_exp,
@@ -390,51 +1214,338 @@ impl Interpreter {
             .map(|expr| self.eval_expression(expr))
             .collect::<Vec<Value>>();
 
+        let named = call_expr
+            .named_arguments
+            .iter()
+            .map(|arg| (arg.name.clone(), self.eval_expression(&arg.value)))
+            .collect::<Vec<(String, Value)>>();
+
+        // Unknown functions in formulas stay symbolic, like `sin[x]` before `sin` is declared
+        if let (Value::Ast(callee), true) = (&var, self.symbolic && named.is_empty()) {
+            let arguments = args.iter().map(Value::to_expression).collect();
+
+            if let Some(expressions) = arguments {
+                return Value::Ast(Expression::CallExpression(Box::new(CallExpression {
+                    node: call_expr.node,
+                    callee: callee.clone(),
+                    arguments: Expression::SequenceExpression(Box::new(SequenceExpression {
+                        node: call_expr.node,
+                        expressions,
+                    })),
+                    named_arguments: vec![],
+                })));
+            }
+        }
+
+        self.call_value_with(var, &args, &named, call_expr)
+    }
+
+    /// Calls a function value, sequences are indexed
+    fn call_value(&mut self, var: Value, args: &[Value], call_expr: &CallExpression) -> Value {
+        self.call_value_with(var, args, &[], call_expr)
+    }
+
+    /// Calls a function value with named arguments, only functions declared with `fn` take them
+    fn call_value_with(
+        &mut self,
+        var: Value,
+        args: &[Value],
+        named: &[(String, Value)],
+        call_expr: &CallExpression,
+    ) -> Value {
+        // The statement is evaluated again once the task is resumed or is left by the throw
+        if self.interrupt.is_some() {
+            return Value::None;
+        }
+
+        if let Some(result) = self.replayed() {
+            return result;
+        }
+
+        if !named.is_empty() && !matches!(var, Value::Function(_)) {
+            self.report(
+                "invalid-arguments",
+                &format!("`{}` doesn't take named arguments", call_expr.callee),
+                call_expr.node.start,
+                call_expr.node.end,
+            );
+            return Value::None;
+        }
+
+        // Statements of the called function are not part of the task
+        let replay = self.replay.take();
+
         let result = match var {
-            Value::NativeFunction(name) => native::run_function(self, &call_expr, &name, &args),
-            Value::Function(declarator) => {
-                if declarator.params.len() != args.len() {
-                    self.report(
-                        &format!(
-                            "Expected {} arguments, got {}",
-                            declarator.params.len(),
-                            args.len()
-                        ),
-                        call_expr.node.start,
-                        call_expr.node.end,
-                    );
-                    unreachable!("Report ends proccess");
-                }
+            Value::NativeFunction(name) => native::run_function(self, call_expr, &name, args),
+            Value::Function(closure) => {
+                let declarator = &closure.declarator;
+
+                let caller_stack = self.enter_env(&closure.env);
                 self.increment_scope();
+                self.declare_params(declarator, args, named, call_expr);
+
+                let result = if declarator.is_generator {
+                    self.new_generator(&closure)
+                } else if declarator.is_async {
+                    self.new_task(&closure)
+                } else {
+                    self.call_stack.push(CallFrame {
+                        name: match declarator.id.as_str() {
+                            "" => "<anonymous>".to_owned(),
+                            id => id.to_owned(),
+                        },
+                        node: call_expr.node,
+                        file: self.file,
+                    });
+                    let caller_file = std::mem::replace(&mut self.file, closure.file);
+
+                    // Only the expression of a formula is symbolic, not the functions it calls
+                    let symbolic = std::mem::take(&mut self.symbolic);
+                    let control = self.eval_block(&declarator.body);
+                    self.symbolic = symbolic;
+
+                    self.file = caller_file;
+                    self.call_stack.pop();
+                    self.decrement_scope();
+
+                    match control {
+                        ControlFlow::Return(val) => val,
+                        ControlFlow::Throw(val) => self.throw(val),
+                        _ => Value::None,
+                    }
+                };
+
+                self.exit_env(caller_stack);
+
+                result
+            }
+
+            Value::Sequence(seq) => {
+                expect_args!(1, self, call_expr, args);
 
-                for (i, variable) in declarator.params.iter().enumerate() {
-                    self.declare_variable(&variable, args[i].clone());
+                match Self::seq_index(seq.len(), &args[0]) {
+                    Ok(index) => seq[index].clone(),
+                    Err(e) => {
+                        self.report("index-error", &e, call_expr.node.start, call_expr.node.end);
+                        return Value::None;
+                    }
                 }
+            }
 
-                let control = self.eval_block(&declarator.body);
+            Value::Range(range) => {
+                expect_args!(1, self, call_expr, args);
 
-                self.decrement_scope();
-                match control {
-                    ControlFlow::Return(val) => val,
-                    ControlFlow::Throw(val) => Value::Err(format!("{}", val)),
-                    _ => Value::None,
+                match Self::seq_index(range.len(), &args[0]) {
+                    Ok(index) => Value::integral(range.nth(index)),
+                    Err(e) => {
+                        self.report("index-error", &e, call_expr.node.start, call_expr.node.end);
+                        return Value::None;
+                    }
                 }
             }
 
             _ => {
                 self.report(
+                    "type-mismatch",
                     &format!("`{}` is not a function", call_expr.callee),
                     call_expr.node.start,
                     call_expr.node.end,
                 );
-                unreachable!("Report ends proccess");
+                return Value::None;
             }
         };
 
+        self.replay = replay;
+        self.record(&result);
+
         result
     }
 
+    /// Declares the parameters in the function scope, missing ones get their default value
+    fn declare_params(
+        &mut self,
+        declarator: &FunctionDeclarator,
+        args: &[Value],
+        named: &[(String, Value)],
+        call_expr: &CallExpression,
+    ) {
+        let positional = declarator.params.iter().filter(|p| !p.is_rest).count();
+        let mut values: Vec<Option<Value>> =
+            args.iter().take(positional).cloned().map(Some).collect();
+        values.resize(positional, None);
+
+        let rest = args.get(positional..).unwrap_or_default().to_vec();
+
+        if !rest.is_empty() && positional == declarator.params.len() {
+            self.report(
+                "invalid-arguments",
+                &format!(
+                    "Expected at most {} argument{} for `{}`, got {}",
+                    positional,
+                    if positional == 1 { "" } else { "s" },
+                    declarator.signature(),
+                    args.len()
+                ),
+                call_expr.node.start,
+                call_expr.node.end,
+            );
+            return;
+        }
+
+        // Named arguments always come from the call expression, in the same order
+        let arg_node = |i: usize| {
+            call_expr
+                .named_arguments
+                .get(i)
+                .map_or(call_expr.node, |arg| arg.node)
+        };
+
+        for (i, (name, value)) in named.iter().enumerate() {
+            let index = declarator
+                .params
+                .iter()
+                .position(|p| &p.name == name && !p.is_rest);
+
+            match index {
+                Some(index) if values[index].is_some() => {
+                    let mut error = self.error(
+                        "invalid-arguments",
+                        &format!(
+                            "Argument `{name}` of `{}` is given twice",
+                            declarator.signature()
+                        ),
+                        arg_node(i),
+                    );
+
+                    match named[..i].iter().position(|(n, _)| n == name) {
+                        Some(first) => error
+                            .labels
+                            .push((arg_node(first), "first given here".to_owned())),
+                        None => error.notes.push(format!(
+                            "`{name}` is also given as positional argument {}",
+                            index + 1
+                        )),
+                    }
+
+                    self.fail(error);
+                    return;
+                }
+                Some(index) => values[index] = Some(value.clone()),
+                None => {
+                    self.report(
+                        "invalid-arguments",
+                        &format!("`{}` has no parameter `{name}`", declarator.signature()),
+                        call_expr.node.start,
+                        call_expr.node.end,
+                    );
+                    return;
+                }
+            }
+        }
+
+        let mut values = values.into_iter();
+
+        for param in &declarator.params {
+            if param.is_rest {
+                self.declare_variable(&param.name, Value::Sequence(rest.clone()));
+                continue;
+            }
+
+            // Defaults are evaluated after the parameters before them are declared
+            let value = match (values.next().unwrap(), &param.default) {
+                (Some(value), _) => value,
+                (None, Some(default)) => self.eval_expression(default),
+                (None, None) => {
+                    self.report(
+                        "invalid-arguments",
+                        &format!(
+                            "Missing argument `{}` for `{}`",
+                            param.name,
+                            declarator.signature()
+                        ),
+                        call_expr.node.start,
+                        call_expr.node.end,
+                    );
+                    return;
+                }
+            };
+
+            self.declare_variable(&param.name, value);
+        }
+    }
+
+    /// Evaluates the body of the first arm whose pattern matches and whose guard is true
+    fn eval_match_expression(&mut self, match_expr: &MatchExpression) -> Value {
+        let value = self.eval_expression(&match_expr.discriminant);
+
+        for arm in &match_expr.arms {
+            if self.interrupt.is_some() {
+                return Value::None;
+            }
+
+            let mut bindings = vec![];
+
+            if !self.match_pattern(&arm.pattern, &value, &mut bindings) {
+                continue;
+            }
+
+            // Bindings of the arm are only visible in its guard and body
+            self.increment_scope();
+
+            for (name, value) in bindings {
+                self.declare_variable(&name, value);
+            }
+
+            let matched = match &arm.guard {
+                Some(guard) => self.eval_expression(guard).as_bool(),
+                None => true,
+            };
+
+            let result = if matched && self.interrupt.is_none() {
+                Some(self.eval_expression(&arm.body))
+            } else {
+                None
+            };
+
+            self.decrement_scope();
+
+            if let Some(result) = result {
+                return result;
+            }
+        }
+
+        if self.interrupt.is_some() {
+            return Value::None;
+        }
+
+        self.report(
+            "unmatched-value",
+            &format!("No arm of `match` matches `{value}`"),
+            match_expr.node.start,
+            match_expr.node.end,
+        );
+        Value::None
+    }
+
+    /// Evaluates only the taken branch
+    fn eval_conditional_expression(&mut self, conditional_expr: &ConditionalExpression) -> Value {
+        if (self.eval_expression(&conditional_expr.test)).as_bool() {
+            self.eval_expression(&conditional_expr.consequent)
+        } else {
+            self.eval_expression(&conditional_expr.alternate)
+        }
+    }
+
     fn eval_unary_expression(&mut self, expression: &UnaryExpression) -> Value {
+        if let Expression::Identifier(id) = &expression.right {
+            match expression.operator {
+                UnaryOperator::PlusPlus | UnaryOperator::MinusMinus => {
+                    return self.eval_update_expression(expression.operator, id)
+                }
+                _ => {}
+            }
+        }
+
         let right = self.eval_expression(&expression.right);
 
         match expression.operator {
@@ -442,9 +1553,40 @@ impl Interpreter {
             UnaryOperator::Minus => -right,
             UnaryOperator::Not => !right,
             UnaryOperator::BitNot => !right,
-            UnaryOperator::PlusPlus => right + Value::Number(1.0),
-            UnaryOperator::MinusMinus => right - Value::Number(1.0),
+            UnaryOperator::PlusPlus => right + Value::Integer(1.into()),
+            UnaryOperator::MinusMinus => right - Value::Integer(1.into()),
+        }
+    }
+
+    /// Increments or decrements the variable and returns its new value
+    fn eval_update_expression(
+        &mut self,
+        operator: UnaryOperator,
+        identifier: &Identifier,
+    ) -> Value {
+        if self.interrupt.is_some() {
+            return Value::None;
+        }
+
+        // Updates are not repeated when a suspended statement is evaluated again
+        if let Some(value) = self.replayed() {
+            return value;
         }
+
+        let Some(var_val) = self.get_variable_value_mut(identifier) else {
+            return Value::None;
+        };
+
+        match operator {
+            UnaryOperator::PlusPlus => *var_val += Value::Integer(1.into()),
+            UnaryOperator::MinusMinus => *var_val -= Value::Integer(1.into()),
+            _ => unreachable!("Only ++ and -- update variables"),
+        }
+
+        let value = var_val.clone();
+        self.record(&value);
+
+        value
     }
 
     fn eval_binary_expression(&mut self, expression: &BinaryExpression) -> Value {
@@ -461,7 +1603,18 @@ impl Interpreter {
             BinaryOperator::Multiply => left * right,
             BinaryOperator::Divide => left / right,
             BinaryOperator::Power => left.pow(&right),
-            BinaryOperator::Range => left.range(&right),
+            BinaryOperator::Range => match (left.as_f64(), right.as_f64()) {
+                (Some(start), Some(end)) if !start.is_finite() || !end.is_finite() => {
+                    self.report(
+                        "out-of-range",
+                        &format!("Range bounds must be finite, got `{left}` and `{right}`"),
+                        expression.node.start,
+                        expression.node.end,
+                    );
+                    Value::None
+                }
+                _ => left.range(&right),
+            },
 
             BinaryOperator::Modulo => left % right,
 
@@ -473,7 +1626,18 @@ impl Interpreter {
             BinaryOperator::BitOr => left.bit_or(&right),
             BinaryOperator::BitXor => left.bit_xor(&right),
 
-            BinaryOperator::BitLeftShift => left << right,
+            BinaryOperator::BitLeftShift => match right.truncated() {
+                Some(shift) if shift > MAX_SHIFT.into() => {
+                    self.report(
+                        "out-of-range",
+                        &format!("Shift amount `{right}` is larger than the limit of {MAX_SHIFT}"),
+                        expression.node.start,
+                        expression.node.end,
+                    );
+                    Value::None
+                }
+                _ => left << right,
+            },
             BinaryOperator::BitRightShift => left >> right,
 
             BinaryOperator::Equal => left.equal(&right),
@@ -489,6 +1653,8 @@ impl Interpreter {
         match &literal.value {
             TokenValue::None => Value::None,
             TokenValue::Number(val) => Value::Number(*val),
+            TokenValue::Integer(val) => Value::Integer(val.clone()),
+            TokenValue::Imaginary(val) => Value::Complex(Complex64::new(0.0, *val)),
             TokenValue::Str(val) => Value::Str(val.clone()),
             TokenValue::Bool(val) => Value::Bool(*val),
             TokenValue::Identifier(id) => self.get_variable_value(&Identifier {
@@ -503,23 +1669,102 @@ impl Interpreter {
         let id = identifier.name.clone();
 
         for scope in self.scope_stack.iter().rev() {
-            let var = self.vault.get(scope).unwrap().values.get(&id);
+            let scope_value = self.vault.get(scope).unwrap();
 
-            match var {
-                Some(var) => return var.clone(),
-                None => {}
+            if let Some(var) = scope_value.values.get(&id) {
+                return match (var, scope_value.formulas.get(&id)) {
+                    (Value::Ast(formula), Some(decl)) => {
+                        let (scope, formula, decl) = (scope.clone(), formula.clone(), *decl);
+                        self.eval_formula(scope, &id, &formula, decl)
+                    }
+                    _ => var.clone(),
+                };
             }
         }
 
+        if self.symbolic {
+            return Value::Ast(Expression::Identifier(identifier.clone()));
+        }
+
         self.report(
+            "undefined-variable",
             &format!("Variable `{identifier}` not found"),
             identifier.node.start,
             identifier.node.end,
         );
-        unreachable!("Report ends proccess");
+        Value::None
+    }
+
+    /// Evaluates the formula of a variable declared with `:=` in the current scope
+    fn eval_formula(
+        &mut self,
+        scope: String,
+        name: &str,
+        formula: &Expression,
+        decl: Node,
+    ) -> Value {
+        let cycle_start = self
+            .formula_stack
+            .iter()
+            .position(|(s, n, _)| *s == scope && n == name);
+
+        if let Some(cycle_start) = cycle_start {
+            let (_, last, last_decl) = self.formula_stack.last().unwrap().clone();
+
+            if last == name {
+                self.report(
+                    "formula-cycle",
+                    &format!("Formula `{name}` depends on itself"),
+                    decl.start,
+                    decl.end,
+                );
+                return Value::None;
+            }
+
+            let cycle = self.formula_stack[cycle_start..]
+                .iter()
+                .map(|(_, n, _)| format!("`{n}`"))
+                .collect::<Vec<_>>()
+                .join(" -> ");
+
+            let mut error = self.error(
+                "formula-cycle",
+                &format!("Formulas depend on each other: {cycle} -> `{name}`"),
+                last_decl,
+            );
+            error
+                .labels
+                .push((decl, format!("`{name}` is declared here")));
+            self.fail(error);
+            return Value::None;
+        }
+
+        self.formula_stack.push((scope, name.to_owned(), decl));
+        let symbolic = std::mem::replace(&mut self.symbolic, true);
+
+        let value = self.eval_expression(formula);
+
+        self.symbolic = symbolic;
+        self.formula_stack.pop();
+
+        value
+    }
+
+    /// Whether the expression is a variable declared with `:=`
+    fn is_formula(&self, expression: &Expression) -> bool {
+        let Expression::Identifier(id) = expression else {
+            return false;
+        };
+
+        self.scope_stack
+            .iter()
+            .rev()
+            .map(|scope| &self.vault[scope])
+            .find(|scope| scope.values.contains_key(&id.name))
+            .is_some_and(|scope| scope.formulas.contains_key(&id.name))
     }
 
-    fn get_cur_value(&mut self, id: &String) -> Value {
+    fn get_cur_value(&mut self, id: &str) -> Value {
         let (scope_name, _) = self.parse_current_scope();
         self.vault
             .get(&format!("{}$0", scope_name))
@@ -530,7 +1775,8 @@ impl Interpreter {
             .clone()
     }
 
-    fn get_variable_value_mut(&mut self, identifier: &Identifier) -> &mut Value {
+    /// Variable to assign to, `None` if it is not declared
+    fn get_variable_value_mut(&mut self, identifier: &Identifier) -> Option<&mut Value> {
         let id = identifier.name.clone();
 
         let mut scope_index = None;
@@ -545,20 +1791,16 @@ impl Interpreter {
         match scope_index {
             Some(index) => {
                 let scope = &self.scope_stack[index];
-                self.vault
-                    .get_mut(scope)
-                    .unwrap()
-                    .values
-                    .get_mut(&id)
-                    .unwrap()
+                self.vault.get_mut(scope).unwrap().values.get_mut(&id)
             }
             None => {
                 self.report(
+                    "undefined-variable",
                     &format!("Variable `{}` not found", id),
                     identifier.node.start,
                     identifier.node.end,
                 );
-                unreachable!("Report ends process");
+                None
             }
         }
     }
@@ -581,9 +1823,12 @@ impl Interpreter {
     //     unreachable!("Report ends proccess");
     // }
 
-    fn declare_variable(&mut self, identifier: &String, value: Value) {
-        self.get_curr_scope_values_mut()
-            .insert(identifier.clone(), value);
+    fn declare_variable(&mut self, identifier: &str, value: Value) {
+        let scope = self.vault.get_mut(&self.current_scope).unwrap();
+
+        // Declaring the variable again with `let` makes it a plain variable
+        scope.formulas.remove(identifier);
+        scope.values.insert(identifier.to_owned(), value);
     }
 
     pub fn initialize(&mut self) {
@@ -599,9 +1844,9 @@ impl Interpreter {
         self.update_current_scope();
 
         // Initialize standard variables
-        self.declare_variable(&"__file__".to_owned(), Value::Str(self.paths[0].clone()));
-        self.declare_variable(&"__name__".to_owned(), Value::Str("main".to_owned()));
-        self.declare_variable(&"__module__".to_owned(), Value::Bool(false));
+        self.declare_variable("__file__", Value::Str(self.paths[0].clone()));
+        self.declare_variable("__name__", Value::Str("main".to_owned()));
+        self.declare_variable("__module__", Value::Bool(false));
 
         //Include std ref to global
         self.send_scope_ref("std$0");
@@ -674,33 +1919,66 @@ impl Interpreter {
 
     /// Increments the current scope
     fn increment_scope(&mut self) {
-        let (scope_name, num) = self.parse_current_scope();
+        let (scope_name, _) = self.parse_current_scope();
 
-        let new_scope = format!("{}${}", scope_name, num + 1);
+        self.scope_count += 1;
+        let new_scope = format!("{}${}", scope_name, self.scope_count);
 
         self.init_scope(new_scope);
     }
 
-    /// Decrements the current scope and deletes named scopes in the current scope
+    /// Decrements the current scope and deletes named scopes in the current scope, captured scopes are kept
     fn decrement_scope(&mut self) {
-        let scope = self.current_scope.clone();
-
-        self.remove_refs(&scope);
-
-        self.vault.remove(&scope);
-        self.scope_stack.pop();
-
+        let scope = self.scope_stack.pop().unwrap();
         self.update_current_scope();
+
+        self.release_scope(&scope);
+        self.maybe_collect();
     }
 
     /// Removes all references and subreferences in scope
     fn remove_refs(&mut self, scope_name: &str) {
         for ref_name in self.get_scope_refs_mut(scope_name).clone() {
+            // Named scopes are also reached through their name, so captured ones are never freed
+            if self.vault[&ref_name].is_captured() {
+                continue;
+            }
+
             self.remove_refs(&ref_name);
             self.vault.remove(&ref_name);
         }
     }
 
+    /// Function value that sees the scopes it is declared in, they are kept after they are left while it exists
+    fn new_closure(&mut self, declarator: &FunctionDeclarator) -> Value {
+        let vault = &mut self.vault;
+        let env = self
+            .scope_stack
+            .iter()
+            .map(|scope| vault.get_mut(scope).unwrap().capture(scope))
+            .collect();
+
+        Value::Function(Box::new(Closure {
+            declarator: declarator.clone(),
+            env,
+            file: self.file,
+        }))
+    }
+
+    /// Replaces the scope stack with the environment of a closure and returns the previous one
+    fn enter_env(&mut self, env: &[Handle]) -> Vec<String> {
+        let env = env.iter().map(|scope| scope.id.clone()).collect();
+        let stack = std::mem::replace(&mut self.scope_stack, env);
+        self.update_current_scope();
+
+        stack
+    }
+
+    fn exit_env(&mut self, stack: Vec<String>) {
+        self.scope_stack = stack;
+        self.update_current_scope();
+    }
+
     /// Initializes the current scope
     fn init_scope(&mut self, scope_name: String) {
         self.vault.insert(scope_name.clone(), ScopeValue::new());
@@ -738,7 +2016,7 @@ impl Interpreter {
             .named_scope_refs
     }
 
-    /// Gets the current named scopes in the current scope
+    // Gets the current named scopes in the current scope
     // fn get_curr_scope_refs(&self) -> &Vec<String> {
     //     &self
     //         .vault
@@ -752,15 +2030,44 @@ impl Interpreter {
         self.current_scope = self.scope_stack.last().unwrap().clone();
     }
 
-    /// Reports an interpreter error
-    fn report(&self, error: &str, start: usize, end: usize) {
-        report_error(
-            self.paths.last().unwrap(),
-            self.sources.last().unwrap(),
-            error,
-            start,
-            end,
-        );
+    /// Throws the value from an expression, the current statement is left once the expression is evaluated
+    fn throw(&mut self, value: Value) -> Value {
+        if !self.failed() {
+            self.interrupt = Some(Interrupt::Throw(value));
+        }
+        Value::None
+    }
+
+    /// Reports an interpreter error, it stops the program and is given back by `run`
+    fn report(&mut self, code: &str, error: &str, start: usize, end: usize) {
+        let error = self.error(code, error, Node::new(start, end));
+        self.fail(error);
+    }
+
+    /// Error at the node of the current file with the calls that led to it
+    fn error(&self, code: &str, message: &str, node: Node) -> RuntimeError {
+        RuntimeError {
+            code: code.to_owned(),
+            message: message.to_owned(),
+            node,
+            path: self.paths[self.file].clone(),
+            source: self.sources[self.file].clone(),
+            traceback: self.traceback(&self.call_stack, node, self.file),
+            labels: vec![],
+            notes: vec![],
+            related: vec![],
+        }
+    }
+
+    /// Stops the program with the error, the first error is kept
+    fn fail(&mut self, error: RuntimeError) {
+        if !self.failed() {
+            self.interrupt = Some(Interrupt::Error(Box::new(error)));
+        }
+    }
+
+    fn failed(&self) -> bool {
+        matches!(self.interrupt, Some(Interrupt::Error(_)))
     }
 
     // fn report_str(&self, error: &str) {
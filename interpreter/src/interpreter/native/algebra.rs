@@ -0,0 +1,155 @@
+use symboscript_optimizer::expressions::{algebra, calculus};
+use symboscript_types::{
+    interpreter::{NativeFunction, Scope, Value},
+    lexer::TokenValue,
+    parser::{self, CallExpression, Expression, Literal},
+};
+
+use crate::expect_args;
+
+use super::Interpreter;
+
+/// simplify[expr]
+pub fn simplify(
+    interpreter: &mut Interpreter,
+    call_expr: &CallExpression,
+    args: &[Value],
+) -> Value {
+    expect_args!(1, interpreter, call_expr, args);
+
+    let expr = get_expr(interpreter, call_expr, &args[0]);
+    from_expression(algebra::simplify(&expr))
+}
+
+/// expand[expr]
+pub fn expand(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &[Value]) -> Value {
+    expect_args!(1, interpreter, call_expr, args);
+
+    let expr = get_expr(interpreter, call_expr, &args[0]);
+    from_expression(algebra::expand(&expr))
+}
+
+/// substitute[expr, name, value], replaces the variable `name` with the value
+pub fn substitute(
+    interpreter: &mut Interpreter,
+    call_expr: &CallExpression,
+    args: &[Value],
+) -> Value {
+    expect_args!(3, interpreter, call_expr, args);
+
+    let expr = get_expr(interpreter, call_expr, &args[0]);
+
+    let name = get_name(interpreter, call_expr, &args[1]);
+
+    let value = get_expr(interpreter, call_expr, &args[2]);
+
+    from_expression(algebra::substitute(&expr, &name, &value))
+}
+
+/// diff[expr, var], derivative of the formula with respect to the variable `var`
+pub fn diff(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &[Value]) -> Value {
+    expect_args!(2, interpreter, call_expr, args);
+
+    let expr = get_expr(interpreter, call_expr, &args[0]);
+    let var = get_name(interpreter, call_expr, &args[1]);
+
+    match calculus::diff(&expr, &var) {
+        Ok(derivative) => from_expression(derivative),
+        Err(part) => {
+            interpreter.report(
+                "not-differentiable",
+                &format!("Can't differentiate `{part}` with respect to `{var}`"),
+                call_expr.node.start,
+                call_expr.node.end,
+            );
+            Value::None
+        }
+    }
+}
+
+/// eval[expr], evaluates the formula with the variables of the current scope
+pub fn eval(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &[Value]) -> Value {
+    expect_args!(1, interpreter, call_expr, args);
+
+    let expr = get_expr(interpreter, call_expr, &args[0]);
+    interpreter.eval_expression(&expr)
+}
+
+pub fn inject(scope: &mut Scope) {
+    scope.insert(
+        "simplify".to_owned(),
+        Value::NativeFunction(NativeFunction::AlgSimplify),
+    );
+
+    scope.insert(
+        "expand".to_owned(),
+        Value::NativeFunction(NativeFunction::AlgExpand),
+    );
+
+    scope.insert(
+        "substitute".to_owned(),
+        Value::NativeFunction(NativeFunction::AlgSubstitute),
+    );
+
+    scope.insert(
+        "diff".to_owned(),
+        Value::NativeFunction(NativeFunction::AlgDiff),
+    );
+
+    scope.insert(
+        "eval".to_owned(),
+        Value::NativeFunction(NativeFunction::AlgEval),
+    );
+}
+
+fn get_expr(
+    interpreter: &mut Interpreter,
+    call_expr: &CallExpression,
+    value: &Value,
+) -> Expression {
+    match value.to_expression() {
+        Some(expr) => expr,
+        None => {
+            interpreter.report(
+                "type-mismatch",
+                &format!("Expected a formula or a number, got `{value}`"),
+                call_expr.node.start,
+                call_expr.node.end,
+            );
+            Expression::None(parser::None {
+                node: call_expr.node,
+            })
+        }
+    }
+}
+
+/// Name of a variable given as a string
+fn get_name(interpreter: &mut Interpreter, call_expr: &CallExpression, value: &Value) -> String {
+    match value {
+        Value::Str(name) => name.clone(),
+        got => {
+            interpreter.report(
+                "type-mismatch",
+                &format!("Variable name must be a string, got `{got}`"),
+                call_expr.node.start,
+                call_expr.node.end,
+            );
+            String::new()
+        }
+    }
+}
+
+/// Formula folded to a single number is given back as a number
+fn from_expression(expr: Expression) -> Value {
+    match expr {
+        Expression::Literal(Literal {
+            value: TokenValue::Number(n),
+            ..
+        }) => Value::Number(n),
+        Expression::Literal(Literal {
+            value: TokenValue::Integer(i),
+            ..
+        }) => Value::Integer(i),
+        expr => Value::Ast(expr),
+    }
+}
@@ -1,43 +1,48 @@
+use num_bigint::BigInt;
+use num_complex::Complex64;
+use num_rational::BigRational;
+use num_traits::{FromPrimitive, Zero};
 use symboscript_types::{
     interpreter::{NativeFunction, Scope, Value},
     parser::CallExpression,
 };
 
-use crate::expect_args;
+use crate::{expect_args, expect_args_range};
 
 use super::Interpreter;
 
 pub fn to_string(
     interpreter: &mut Interpreter,
     call_expr: &CallExpression,
-    args: &Vec<Value>,
+    args: &[Value],
 ) -> Value {
     expect_args!(0, interpreter, call_expr, args);
 
-    let value = interpreter.get_cur_value(&"$value".to_owned());
+    let value = interpreter.get_cur_value("$value");
 
     match value.clone() {
-        Value::Str(_) => return value,
-        Value::None => return Value::Str("None".to_owned()),
-        Value::Number(n) => return Value::Str(n.to_string()),
-        Value::Bool(b) => return Value::Str(b.to_string()),
-        Value::Sequence(_) => todo!(),
-        Value::Ast(_) => todo!(),
-        Value::ScopeRef(sref) => return Value::Str(sref),
-        Value::NativeFunction(_) => todo!(),
-        Value::Function(_) => todo!(),
-        Value::Err(_) => todo!(),
+        Value::Str(_) => value,
+        Value::None => Value::Str("None".to_owned()),
+        Value::Number(_) | Value::Integer(_) | Value::Rational(_) | Value::Complex(_) => {
+            Value::Str(value.to_string())
+        }
+        Value::Bool(b) => Value::Str(b.to_string()),
+        Value::Sequence(_)
+        | Value::Range(_)
+        | Value::Generator(_)
+        | Value::Future(_)
+        | Value::Ast(_)
+        | Value::NativeFunction(_)
+        | Value::Function(_)
+        | Value::Err(_) => Value::Str(value.to_string()),
+        Value::ScopeRef(sref) => Value::Str(sref),
     }
 }
 
-pub fn is_err(
-    interpreter: &mut Interpreter,
-    call_expr: &CallExpression,
-    args: &Vec<Value>,
-) -> Value {
+pub fn is_err(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &[Value]) -> Value {
     expect_args!(0, interpreter, call_expr, args);
 
-    let value = interpreter.get_cur_value(&"$value".to_owned());
+    let value = interpreter.get_cur_value("$value");
 
     match value {
         Value::Err(_) => Value::Bool(true),
@@ -45,6 +50,129 @@ pub fn is_err(
     }
 }
 
+/// int[x], drops the fraction of numbers and parses strings
+pub fn to_int(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &[Value]) -> Value {
+    expect_args!(1, interpreter, call_expr, args);
+
+    let int = match &args[0] {
+        Value::Integer(i) => Some(i.clone()),
+        Value::Rational(r) => Some(r.to_integer()),
+        Value::Number(n) => BigInt::from_f64(n.trunc()),
+        Value::Str(s) => s.trim().parse::<BigInt>().ok(),
+        _ => None,
+    };
+
+    match int {
+        Some(int) => Value::Integer(int),
+        None => conversion_error(interpreter, call_expr, &args[0], "an integer"),
+    }
+}
+
+/// float[x]
+pub fn to_float(
+    interpreter: &mut Interpreter,
+    call_expr: &CallExpression,
+    args: &[Value],
+) -> Value {
+    expect_args!(1, interpreter, call_expr, args);
+
+    let float = match &args[0] {
+        Value::Str(s) => s.trim().parse::<f64>().ok(),
+        value => value.as_f64(),
+    };
+
+    match float {
+        Some(float) => Value::Number(float),
+        None => conversion_error(interpreter, call_expr, &args[0], "a float"),
+    }
+}
+
+/// rational[x] or rational[numerator, denominator], floats are taken as the decimal they are written as
+pub fn to_rational(
+    interpreter: &mut Interpreter,
+    call_expr: &CallExpression,
+    args: &[Value],
+) -> Value {
+    expect_args_range!(1, 2, interpreter, call_expr, args);
+
+    if let [numerator, denominator] = args {
+        return match (numerator.to_rational(), denominator.to_rational()) {
+            (Some(n), Some(d)) if !d.is_zero() => Value::rational(n / d),
+            _ => {
+                interpreter.report(
+                    "type-mismatch",
+                    &format!(
+                        "Expected an exact numerator and a non zero denominator, got `{numerator}` and `{denominator}`"
+                    ),
+                    call_expr.node.start,
+                    call_expr.node.end,
+                );
+                return Value::None;
+            }
+        };
+    }
+
+    let rational = match &args[0] {
+        Value::Integer(_) | Value::Rational(_) => args[0].to_rational(),
+        Value::Number(n) => parse_rational(&n.to_string()),
+        Value::Str(s) => parse_rational(s.trim()),
+        _ => None,
+    };
+
+    match rational {
+        Some(rational) => Value::rational(rational),
+        None => conversion_error(interpreter, call_expr, &args[0], "a rational"),
+    }
+}
+
+/// complex[re, im?]
+pub fn to_complex(
+    interpreter: &mut Interpreter,
+    call_expr: &CallExpression,
+    args: &[Value],
+) -> Value {
+    expect_args_range!(1, 2, interpreter, call_expr, args);
+
+    let im = args.get(1).cloned().unwrap_or(Value::Integer(0.into()));
+
+    match (args[0].as_f64(), im.as_f64()) {
+        (Some(re), Some(im)) => Value::Complex(Complex64::new(re, im)),
+        _ => {
+            let re = &args[0];
+
+            interpreter.report(
+                "type-mismatch",
+                &format!("Expected real numbers, got `{re}` and `{im}`"),
+                call_expr.node.start,
+                call_expr.node.end,
+            );
+            Value::None
+        }
+    }
+}
+
+pub fn inject(scope: &mut Scope) {
+    scope.insert(
+        "int".to_owned(),
+        Value::NativeFunction(NativeFunction::ToInt),
+    );
+
+    scope.insert(
+        "float".to_owned(),
+        Value::NativeFunction(NativeFunction::ToFloat),
+    );
+
+    scope.insert(
+        "rational".to_owned(),
+        Value::NativeFunction(NativeFunction::ToRational),
+    );
+
+    scope.insert(
+        "complex".to_owned(),
+        Value::NativeFunction(NativeFunction::ToComplex),
+    );
+}
+
 pub fn inject_methods(scope: &mut Scope) {
     scope.insert(
         "to_string".to_owned(),
@@ -56,3 +184,45 @@ pub fn inject_methods(scope: &mut Scope) {
         Value::NativeFunction(NativeFunction::IsError),
     );
 }
+
+/// Parses `3`, `-1.25` or `2/3`
+fn parse_rational(s: &str) -> Option<BigRational> {
+    if let Some((numerator, denominator)) = s.split_once('/') {
+        let denominator = denominator.trim().parse::<BigInt>().ok()?;
+
+        if denominator.is_zero() {
+            return None;
+        }
+
+        return Some(BigRational::new(
+            numerator.trim().parse::<BigInt>().ok()?,
+            denominator,
+        ));
+    }
+
+    let (whole, fraction) = s.split_once('.').unwrap_or((s, ""));
+
+    if !fraction.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    let numerator = format!("{whole}{fraction}").parse::<BigInt>().ok()?;
+    let denominator = BigInt::from(10).pow(fraction.len() as u32);
+
+    Some(BigRational::new(numerator, denominator))
+}
+
+fn conversion_error(
+    interpreter: &mut Interpreter,
+    call_expr: &CallExpression,
+    value: &Value,
+    to: &str,
+) -> Value {
+    interpreter.report(
+        "type-mismatch",
+        &format!("Can't convert `{value}` to {to}"),
+        call_expr.node.start,
+        call_expr.node.end,
+    );
+    Value::None
+}
@@ -0,0 +1,102 @@
+use symboscript_types::{
+    interpreter::{ErrorValue, NativeFunction, Scope, Value},
+    parser::CallExpression,
+};
+
+use crate::{expect_args, expect_args_range};
+
+use super::Interpreter;
+
+/// error[kind, message, payload?]
+pub fn new(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &[Value]) -> Value {
+    expect_args_range!(2, 3, interpreter, call_expr, args);
+
+    Value::Err(Box::new(ErrorValue {
+        kind: args[0].to_string(),
+        message: args[1].to_string(),
+        payload: args.get(2).cloned().unwrap_or(Value::None),
+        span: None,
+        file: 0,
+        stack: vec![],
+    }))
+}
+
+pub fn message(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &[Value]) -> Value {
+    expect_args!(0, interpreter, call_expr, args);
+
+    Value::Str(get_err(interpreter).message)
+}
+
+pub fn kind(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &[Value]) -> Value {
+    expect_args!(0, interpreter, call_expr, args);
+
+    Value::Str(get_err(interpreter).kind)
+}
+
+pub fn payload(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &[Value]) -> Value {
+    expect_args!(0, interpreter, call_expr, args);
+
+    get_err(interpreter).payload
+}
+
+/// `[start, end]` offsets of the `throw`, `None` if the error is not thrown yet
+pub fn span(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &[Value]) -> Value {
+    expect_args!(0, interpreter, call_expr, args);
+
+    match get_err(interpreter).span {
+        Some(node) => Value::Sequence(vec![
+            Value::Integer(node.start.into()),
+            Value::Integer(node.end.into()),
+        ]),
+        None => Value::None,
+    }
+}
+
+/// Throws the error again, it keeps the span and the call stack of the first throw
+pub fn rethrow(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &[Value]) {
+    expect_args!(0, interpreter, call_expr, args);
+
+    let err = get_err(interpreter);
+    interpreter.throw(Value::Err(Box::new(err)));
+}
+
+pub fn inject(scope: &mut Scope) {
+    scope.insert(
+        "error".to_owned(),
+        Value::NativeFunction(NativeFunction::ErrNew),
+    );
+}
+
+pub fn inject_methods(scope: &mut Scope) {
+    scope.insert(
+        "message".to_owned(),
+        Value::NativeFunction(NativeFunction::ErrMessage),
+    );
+
+    scope.insert(
+        "kind".to_owned(),
+        Value::NativeFunction(NativeFunction::ErrKind),
+    );
+
+    scope.insert(
+        "payload".to_owned(),
+        Value::NativeFunction(NativeFunction::ErrPayload),
+    );
+
+    scope.insert(
+        "span".to_owned(),
+        Value::NativeFunction(NativeFunction::ErrSpan),
+    );
+
+    scope.insert(
+        "rethrow".to_owned(),
+        Value::NativeFunction(NativeFunction::ErrRethrow),
+    );
+}
+
+fn get_err(interpreter: &mut Interpreter) -> ErrorValue {
+    match interpreter.get_cur_value("$value") {
+        Value::Err(err) => *err,
+        _ => unreachable!("Error methods are only called on errors"),
+    }
+}
@@ -0,0 +1,71 @@
+use symboscript_types::{
+    interpreter::{NativeFunction, Scope, Value},
+    parser::CallExpression,
+};
+
+use crate::expect_args;
+
+use super::Interpreter;
+
+/// Resumes the generator, `None` once it is finished
+///
+/// A finished generator looks the same as one yielding `None`,
+/// `done[]` right after tells them apart
+pub fn next(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &[Value]) -> Value {
+    expect_args!(0, interpreter, call_expr, args);
+
+    let id = get_id(interpreter);
+
+    interpreter.resume_generator(&id).unwrap_or(Value::None)
+}
+
+/// Whether the generator has run to its end, known once `next[]` resumed it past its last `yield`
+pub fn done(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &[Value]) -> Value {
+    expect_args!(0, interpreter, call_expr, args);
+
+    let id = get_id(interpreter);
+
+    Value::Bool(!interpreter.generators.contains_key(&id))
+}
+
+/// Collects the values left in the generator
+pub fn to_sequence(
+    interpreter: &mut Interpreter,
+    call_expr: &CallExpression,
+    args: &[Value],
+) -> Value {
+    expect_args!(0, interpreter, call_expr, args);
+
+    let id = get_id(interpreter);
+    let mut seq = vec![];
+
+    while let Some(value) = interpreter.resume_generator(&id) {
+        seq.push(value);
+    }
+
+    Value::Sequence(seq)
+}
+
+pub fn inject(scope: &mut Scope) {
+    scope.insert(
+        "next".to_owned(),
+        Value::NativeFunction(NativeFunction::GenNext),
+    );
+
+    scope.insert(
+        "done".to_owned(),
+        Value::NativeFunction(NativeFunction::GenDone),
+    );
+
+    scope.insert(
+        "to_sequence".to_owned(),
+        Value::NativeFunction(NativeFunction::GenToSequence),
+    );
+}
+
+fn get_id(interpreter: &mut Interpreter) -> String {
+    match interpreter.get_cur_value("$value") {
+        Value::Generator(generator) => generator.id,
+        _ => unreachable!("Generator methods are only called on generators"),
+    }
+}
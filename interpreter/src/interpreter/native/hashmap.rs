@@ -7,14 +7,16 @@ use crate::{expect_args, mut_values_hm};
 
 use super::Interpreter;
 
-pub fn set(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &Vec<Value>) {
+pub fn set(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &[Value]) {
     expect_args!(3, interpreter, call_expr, args);
 
     let scope_ref = args[0].clone();
     let key = args[1].clone();
     let value = args[2].clone();
 
-    let scope = match_scope(&scope_ref, interpreter, call_expr);
+    let Some(scope) = match_scope(&scope_ref, interpreter, call_expr) else {
+        return;
+    };
 
     interpreter
         .vault
@@ -24,62 +26,56 @@ pub fn set(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &Vec
         .insert(key.to_string(), value);
 }
 
-pub fn del(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &Vec<Value>) {
+pub fn del(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &[Value]) {
     let values = mut_values_hm!(2, interpreter, call_expr, args);
 
     values.remove(&args[1].to_string());
 }
 
-pub fn has(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &Vec<Value>) -> Value {
+pub fn has(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &[Value]) -> Value {
     let values = mut_values_hm!(2, interpreter, call_expr, args);
 
     Value::Bool(values.contains_key(&args[1].to_string()))
 }
 
-pub fn len(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &Vec<Value>) -> Value {
+pub fn len(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &[Value]) -> Value {
     let values = mut_values_hm!(1, interpreter, call_expr, args);
 
-    Value::Number(values.len() as f64)
+    Value::Integer(values.len().into())
 }
 
-pub fn keys(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &Vec<Value>) -> Value {
+pub fn keys(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &[Value]) -> Value {
     let values = mut_values_hm!(1, interpreter, call_expr, args);
 
     Value::Sequence(
-        values
-            .keys()
-            .filter(|v| match v.as_str() {
-                // TODO: Remove this when arrays will be implemented in language and add this to native/lang/hashmap.syms(.rs)
-                "this" | "set" | "get" | "del" | "has" | "len" | "keys" | "values" | "clear" => {
-                    false
-                }
-                _ => true,
-            })
-            .map(|k| Value::Str(k.to_string()))
+        entries(values)
+            .map(|(k, _)| Value::Str(k.to_string()))
             .collect(),
     )
 }
 
-pub fn values(
-    interpreter: &mut Interpreter,
-    call_expr: &CallExpression,
-    args: &Vec<Value>,
-) -> Value {
+/// Key-value pairs stored in the hashmap, without its methods
+pub fn entries(values: &Scope) -> impl Iterator<Item = (&String, &Value)> {
+    values.iter().filter(|(k, _)| match k.as_str() {
+        // TODO: Remove this when arrays will be implemented in language and add this to native/lang/hashmap.syms(.rs)
+        "this" | "set" | "get" | "del" | "has" | "len" | "keys" | "values" | "clear" => false,
+        _ => true,
+    })
+}
+
+pub fn values(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &[Value]) -> Value {
     let values = mut_values_hm!(1, interpreter, call_expr, args);
 
     Value::Sequence(
         values
             .values()
+            .filter(|&v| !matches!(v, Value::Function(_) | Value::ScopeRef(_)))
             .cloned()
-            .filter(|v| match v {
-                Value::Function(_) | Value::ScopeRef(_) => false,
-                _ => true,
-            })
             .collect(),
     )
 }
 
-pub fn clear(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &Vec<Value>) {
+pub fn clear(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &[Value]) {
     let values = mut_values_hm!(1, interpreter, call_expr, args);
 
     for key in values.clone().keys() {
@@ -96,7 +92,7 @@ pub fn clear(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &V
     }
 }
 
-pub fn get(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &Vec<Value>) -> Value {
+pub fn get(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &[Value]) -> Value {
     let values = mut_values_hm!(2, interpreter, call_expr, args);
     let val = values.get(&args[1].to_string());
 
@@ -106,13 +102,14 @@ pub fn get(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &Vec
     }
 }
 
-pub fn new(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &Vec<Value>) -> Value {
+pub fn new(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &[Value]) -> Value {
     expect_args!(0, interpreter, call_expr, args);
 
     let scope = interpreter.start_declaration_of_id_scope();
-    interpreter.declare_variable(&"this".to_owned(), Value::ScopeRef(scope.clone()));
+    interpreter.declare_variable("this", Value::ScopeRef(scope.clone()));
 
-    interpreter.eval_ast(interpreter.std_lang.hashmap.clone());
+    let hashmap = interpreter.std_lang.hashmap.clone();
+    interpreter.eval_block(&hashmap.program.body);
     interpreter.end_declaration_of_named_scope(&scope);
 
     Value::ScopeRef(scope)
@@ -169,16 +166,17 @@ fn match_scope(
     scope_ref: &Value,
     interpreter: &mut Interpreter,
     call_expr: &CallExpression,
-) -> String {
+) -> Option<String> {
     match scope_ref {
-        Value::ScopeRef(ref_name) => ref_name.to_string(),
+        Value::ScopeRef(ref_name) => Some(ref_name.to_string()),
         got => {
             interpreter.report(
+                "type-mismatch",
                 format!("{} is not a scope reference", got).as_str(),
                 call_expr.node.start,
                 call_expr.node.end,
             );
-            unreachable!("Report ends proccess");
+            None
         }
     }
 }
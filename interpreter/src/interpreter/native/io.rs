@@ -1,30 +1,26 @@
 use colored::Colorize;
 use symboscript_types::interpreter::{NativeFunction, Scope, Value};
 
-pub fn println(s: &Vec<Value>) {
+pub fn println(s: &[Value]) {
     print(s);
     println!();
 }
 
-pub fn print(s: &Vec<Value>) {
+pub fn print(s: &[Value]) {
     for val in s {
         match val {
             Value::None => print!("{}", "None".blue().bold()),
-            Value::Number(n) => print!("{}", n.to_string().green()),
+            Value::Number(_) | Value::Integer(_) | Value::Rational(_) | Value::Complex(_) => {
+                print!("{}", val.to_string().green())
+            }
             Value::Bool(b) => print!("{}", b.to_string().blue().bold()),
             Value::Str(str) => print!("{}", str),
-            Value::Sequence(seq) => {
-                print!("[");
-                for val in seq {
-                    print!("{}, ", val);
-                }
-                print!("]");
-            }
+            Value::Sequence(_) | Value::Range(_) => print!("{}", val),
             Value::Ast(v) => print!("{}", v),
             Value::ScopeRef(v) => print!("{}", v),
-            Value::NativeFunction(_) => todo!(),
-            Value::Function(v) => print!("{}", v),
-            Value::Err(e) => print!("{}", e),
+            Value::NativeFunction(_) | Value::Generator(_) | Value::Future(_) => print!("{}", val),
+            Value::Function(v) => print!("{}", v.declarator),
+            Value::Err(_) => print!("{}", val.to_string().red()),
         }
 
         print!(" ")
@@ -16,4 +16,5 @@ pub fn value() -> Ast {
         ",
     )
     .parse()
+    .expect("Std hashmap source is valid")
 }
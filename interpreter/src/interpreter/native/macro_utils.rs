@@ -3,10 +3,27 @@ macro_rules! expect_args {
     ($amount: expr, $interpreter:ident, $call_expr:ident, $args:ident ) => {
         if $args.len() != $amount {
             $interpreter.report(
-                &format!("Wrong number of arguments (expected $amount)"),
+                "invalid-arguments",
+                &format!("Wrong number of arguments (expected {})", $amount),
                 $call_expr.node.start,
                 $call_expr.node.end,
             );
+            return Default::default();
+        }
+    };
+}
+
+#[macro_export]
+macro_rules! expect_args_range {
+    ($min: expr, $max: expr, $interpreter:ident, $call_expr:ident, $args:ident ) => {
+        if !($min..=$max).contains(&$args.len()) {
+            $interpreter.report(
+                "invalid-arguments",
+                &format!("Wrong number of arguments (expected {} to {})", $min, $max),
+                $call_expr.node.start,
+                $call_expr.node.end,
+            );
+            return Default::default();
         }
     };
 }
@@ -18,7 +35,9 @@ macro_rules! mut_values_hm {
 
         let scope_ref = $args[0].clone();
 
-        let scope = match_scope(&scope_ref, $interpreter, $call_expr);
+        let Some(scope) = match_scope(&scope_ref, $interpreter, $call_expr) else {
+            return Default::default();
+        };
 
         &mut $interpreter.vault.get_mut(&scope).unwrap().values
     }};
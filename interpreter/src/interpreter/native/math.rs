@@ -0,0 +1,470 @@
+use std::{
+    cmp::Ordering,
+    f64::consts::{E, LN_10, LN_2, PI, TAU},
+};
+
+use num_bigint::Sign;
+use num_complex::Complex64;
+use num_rational::BigRational;
+use num_traits::{Signed, ToPrimitive};
+use rand::Rng;
+use symboscript_types::{
+    interpreter::{NativeFunction, Scope, Value},
+    parser::{CallExpression, Expression, Identifier, MemberExpression, SequenceExpression},
+};
+
+use crate::{expect_args, expect_args_range};
+
+use super::Interpreter;
+
+/// sqrt[x], perfect squares stay exact and negative numbers give complex roots
+pub fn sqrt(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &[Value]) -> Value {
+    expect_args!(1, interpreter, call_expr, args);
+
+    match &args[0] {
+        Value::Integer(n) if n.sign() != Sign::Minus && n.sqrt().pow(2) == *n => {
+            Value::Integer(n.sqrt())
+        }
+        value if value.as_f64().is_some_and(|n| n < 0.0) => {
+            Value::Complex(Complex64::new(0.0, (-value.as_f64().unwrap()).sqrt()))
+        }
+        _ => unary(interpreter, call_expr, args, f64::sqrt, |c| c.sqrt()),
+    }
+}
+
+pub fn cbrt(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &[Value]) -> Value {
+    unary(interpreter, call_expr, args, f64::cbrt, |c| c.cbrt())
+}
+
+pub fn exp(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &[Value]) -> Value {
+    unary(interpreter, call_expr, args, f64::exp, |c| c.exp())
+}
+
+pub fn ln(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &[Value]) -> Value {
+    unary(interpreter, call_expr, args, f64::ln, |c| c.ln())
+}
+
+/// log[x, base?], natural logarithm without a base
+pub fn log(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &[Value]) -> Value {
+    expect_args_range!(1, 2, interpreter, call_expr, args);
+
+    let base = match args.get(1) {
+        Some(base) => base,
+        None => return ln(interpreter, call_expr, args),
+    };
+
+    if let Some(value) = symbolic(call_expr, args) {
+        return value;
+    }
+
+    match (&args[0], base) {
+        (Value::Complex(_), _) | (_, Value::Complex(_)) => {
+            let x = get_complex(interpreter, call_expr, &args[0]);
+            let base = get_complex(interpreter, call_expr, base);
+
+            Value::Complex(x.ln() / base.ln())
+        }
+        (x, base) => {
+            let x = get_real(interpreter, call_expr, x);
+            let base = get_real(interpreter, call_expr, base);
+
+            Value::Number(x.log(base))
+        }
+    }
+}
+
+pub fn log2(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &[Value]) -> Value {
+    unary(interpreter, call_expr, args, f64::log2, |c| c.ln() / LN_2)
+}
+
+pub fn log10(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &[Value]) -> Value {
+    unary(interpreter, call_expr, args, f64::log10, |c| c.ln() / LN_10)
+}
+
+pub fn sin(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &[Value]) -> Value {
+    unary(interpreter, call_expr, args, f64::sin, |c| c.sin())
+}
+
+pub fn cos(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &[Value]) -> Value {
+    unary(interpreter, call_expr, args, f64::cos, |c| c.cos())
+}
+
+pub fn tan(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &[Value]) -> Value {
+    unary(interpreter, call_expr, args, f64::tan, |c| c.tan())
+}
+
+pub fn asin(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &[Value]) -> Value {
+    unary(interpreter, call_expr, args, f64::asin, |c| c.asin())
+}
+
+pub fn acos(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &[Value]) -> Value {
+    unary(interpreter, call_expr, args, f64::acos, |c| c.acos())
+}
+
+pub fn atan(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &[Value]) -> Value {
+    unary(interpreter, call_expr, args, f64::atan, |c| c.atan())
+}
+
+/// atan2[y, x]
+pub fn atan2(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &[Value]) -> Value {
+    binary(interpreter, call_expr, args, f64::atan2)
+}
+
+pub fn sinh(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &[Value]) -> Value {
+    unary(interpreter, call_expr, args, f64::sinh, |c| c.sinh())
+}
+
+pub fn cosh(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &[Value]) -> Value {
+    unary(interpreter, call_expr, args, f64::cosh, |c| c.cosh())
+}
+
+pub fn tanh(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &[Value]) -> Value {
+    unary(interpreter, call_expr, args, f64::tanh, |c| c.tanh())
+}
+
+/// abs[x], the modulus for complex numbers
+pub fn abs(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &[Value]) -> Value {
+    expect_args!(1, interpreter, call_expr, args);
+
+    match &args[0] {
+        Value::Ast(_) => symbolic(call_expr, args).unwrap_or(Value::None),
+        Value::Integer(i) => Value::Integer(i.abs()),
+        Value::Rational(r) => Value::Rational(r.abs()),
+        Value::Complex(c) => Value::Number(c.norm()),
+        value => Value::Number(get_real(interpreter, call_expr, value).abs()),
+    }
+}
+
+pub fn floor(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &[Value]) -> Value {
+    rounding(interpreter, call_expr, args, BigRational::floor, f64::floor)
+}
+
+pub fn ceil(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &[Value]) -> Value {
+    rounding(interpreter, call_expr, args, BigRational::ceil, f64::ceil)
+}
+
+/// round[x], halves are rounded away from zero
+pub fn round(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &[Value]) -> Value {
+    rounding(interpreter, call_expr, args, BigRational::round, f64::round)
+}
+
+pub fn trunc(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &[Value]) -> Value {
+    rounding(interpreter, call_expr, args, BigRational::trunc, f64::trunc)
+}
+
+/// sign[x], -1, 0 or 1
+pub fn sign(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &[Value]) -> Value {
+    expect_args!(1, interpreter, call_expr, args);
+
+    get_real(interpreter, call_expr, &args[0]);
+
+    match args[0].compare(&Value::Integer(0.into())) {
+        Some(order) => Value::Integer((order as i8).into()),
+        None => Value::Number(f64::NAN),
+    }
+}
+
+/// min[x, ...]
+pub fn min(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &[Value]) -> Value {
+    extremum(interpreter, call_expr, args, Ordering::Less)
+}
+
+/// max[x, ...]
+pub fn max(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &[Value]) -> Value {
+    extremum(interpreter, call_expr, args, Ordering::Greater)
+}
+
+/// hypot[x, y]
+pub fn hypot(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &[Value]) -> Value {
+    binary(interpreter, call_expr, args, f64::hypot)
+}
+
+/// random[], float from 0 to 1 excluding 1
+pub fn random(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &[Value]) -> Value {
+    expect_args!(0, interpreter, call_expr, args);
+
+    Value::Number(rand::thread_rng().gen::<f64>())
+}
+
+/// random_int[min, max], both bounds are included
+pub fn random_int(
+    interpreter: &mut Interpreter,
+    call_expr: &CallExpression,
+    args: &[Value],
+) -> Value {
+    expect_args!(2, interpreter, call_expr, args);
+
+    let bound = |value: &Value| value.to_integer().and_then(|n| n.to_i64());
+
+    match (bound(&args[0]), bound(&args[1])) {
+        (Some(min), Some(max)) if min <= max => {
+            Value::Integer(rand::thread_rng().gen_range(min..=max).into())
+        }
+        _ => {
+            let (min, max) = (&args[0], &args[1]);
+
+            interpreter.report(
+                "out-of-range",
+                &format!("Expected integer bounds with min <= max, got `{min}` and `{max}`"),
+                call_expr.node.start,
+                call_expr.node.end,
+            );
+            Value::None
+        }
+    }
+}
+
+/// re[z], real part
+pub fn re(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &[Value]) -> Value {
+    expect_args!(1, interpreter, call_expr, args);
+
+    match &args[0] {
+        Value::Complex(c) => Value::Number(c.re),
+        value => {
+            get_real(interpreter, call_expr, value);
+            value.clone()
+        }
+    }
+}
+
+/// im[z], imaginary part
+pub fn im(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &[Value]) -> Value {
+    expect_args!(1, interpreter, call_expr, args);
+
+    match &args[0] {
+        Value::Complex(c) => Value::Number(c.im),
+        value => {
+            get_real(interpreter, call_expr, value);
+            Value::Integer(0.into())
+        }
+    }
+}
+
+/// conj[z], complex conjugate
+pub fn conj(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &[Value]) -> Value {
+    expect_args!(1, interpreter, call_expr, args);
+
+    match &args[0] {
+        Value::Complex(c) => Value::Complex(c.conj()),
+        value => {
+            get_real(interpreter, call_expr, value);
+            value.clone()
+        }
+    }
+}
+
+/// arg[z], angle of the complex number
+pub fn arg(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &[Value]) -> Value {
+    expect_args!(1, interpreter, call_expr, args);
+
+    Value::Number(get_complex(interpreter, call_expr, &args[0]).arg())
+}
+
+pub fn inject(scope: &mut Scope) {
+    scope.insert("pi".to_owned(), Value::Number(PI));
+    scope.insert("e".to_owned(), Value::Number(E));
+    scope.insert("tau".to_owned(), Value::Number(TAU));
+    scope.insert("inf".to_owned(), Value::Number(f64::INFINITY));
+    scope.insert("nan".to_owned(), Value::Number(f64::NAN));
+    scope.insert("i".to_owned(), Value::Complex(Complex64::i()));
+
+    for (name, function) in [
+        ("sqrt", NativeFunction::MathSqrt),
+        ("cbrt", NativeFunction::MathCbrt),
+        ("exp", NativeFunction::MathExp),
+        ("ln", NativeFunction::MathLn),
+        ("log", NativeFunction::MathLog),
+        ("log2", NativeFunction::MathLog2),
+        ("log10", NativeFunction::MathLog10),
+        ("sin", NativeFunction::MathSin),
+        ("cos", NativeFunction::MathCos),
+        ("tan", NativeFunction::MathTan),
+        ("asin", NativeFunction::MathAsin),
+        ("acos", NativeFunction::MathAcos),
+        ("atan", NativeFunction::MathAtan),
+        ("atan2", NativeFunction::MathAtan2),
+        ("sinh", NativeFunction::MathSinh),
+        ("cosh", NativeFunction::MathCosh),
+        ("tanh", NativeFunction::MathTanh),
+        ("abs", NativeFunction::MathAbs),
+        ("floor", NativeFunction::MathFloor),
+        ("ceil", NativeFunction::MathCeil),
+        ("round", NativeFunction::MathRound),
+        ("trunc", NativeFunction::MathTrunc),
+        ("sign", NativeFunction::MathSign),
+        ("min", NativeFunction::MathMin),
+        ("max", NativeFunction::MathMax),
+        ("hypot", NativeFunction::MathHypot),
+        ("random", NativeFunction::MathRandom),
+        ("random_int", NativeFunction::MathRandomInt),
+        ("re", NativeFunction::MathRe),
+        ("im", NativeFunction::MathIm),
+        ("conj", NativeFunction::MathConj),
+        ("arg", NativeFunction::MathArg),
+    ] {
+        scope.insert(name.to_owned(), Value::NativeFunction(function));
+    }
+}
+
+/// Function of one number, complex numbers use `complex` and formulas stay symbolic
+fn unary(
+    interpreter: &mut Interpreter,
+    call_expr: &CallExpression,
+    args: &[Value],
+    real: fn(f64) -> f64,
+    complex: fn(Complex64) -> Complex64,
+) -> Value {
+    expect_args!(1, interpreter, call_expr, args);
+
+    match &args[0] {
+        Value::Ast(_) => symbolic(call_expr, args).unwrap_or(Value::None),
+        Value::Complex(c) => Value::Complex(complex(*c)),
+        value => Value::Number(real(get_real(interpreter, call_expr, value))),
+    }
+}
+
+/// Function of two real numbers
+fn binary(
+    interpreter: &mut Interpreter,
+    call_expr: &CallExpression,
+    args: &[Value],
+    real: fn(f64, f64) -> f64,
+) -> Value {
+    expect_args!(2, interpreter, call_expr, args);
+
+    if let Some(value) = symbolic(call_expr, args) {
+        return value;
+    }
+
+    let a = get_real(interpreter, call_expr, &args[0]);
+    let b = get_real(interpreter, call_expr, &args[1]);
+
+    Value::Number(real(a, b))
+}
+
+/// Exact numbers are rounded exactly, floats become integers when they are finite
+fn rounding(
+    interpreter: &mut Interpreter,
+    call_expr: &CallExpression,
+    args: &[Value],
+    exact: fn(&BigRational) -> BigRational,
+    float: fn(f64) -> f64,
+) -> Value {
+    expect_args!(1, interpreter, call_expr, args);
+
+    match &args[0] {
+        Value::Ast(_) => symbolic(call_expr, args).unwrap_or(Value::None),
+        Value::Integer(_) => args[0].clone(),
+        Value::Rational(r) => Value::Integer(exact(r).to_integer()),
+        value => Value::integral(float(get_real(interpreter, call_expr, value))),
+    }
+}
+
+fn extremum(
+    interpreter: &mut Interpreter,
+    call_expr: &CallExpression,
+    args: &[Value],
+    order: Ordering,
+) -> Value {
+    if args.is_empty() {
+        interpreter.report(
+            "invalid-arguments",
+            "Wrong number of arguments (expected at least 1)",
+            call_expr.node.start,
+            call_expr.node.end,
+        );
+        return Value::None;
+    }
+
+    let mut best = &args[0];
+    get_real(interpreter, call_expr, best);
+
+    for value in &args[1..] {
+        get_real(interpreter, call_expr, value);
+
+        if value.compare(best) == Some(order) {
+            best = value;
+        }
+    }
+
+    best.clone()
+}
+
+/// Call with a formula argument is kept as a formula, like `math.sin[x]`
+fn symbolic(call_expr: &CallExpression, args: &[Value]) -> Option<Value> {
+    if !args.iter().any(|arg| matches!(arg, Value::Ast(_))) {
+        return None;
+    }
+
+    let expressions = args
+        .iter()
+        .map(Value::to_expression)
+        .collect::<Option<Vec<Expression>>>()?;
+
+    Some(Value::Ast(Expression::CallExpression(Box::new(
+        CallExpression {
+            node: call_expr.node,
+            callee: math_callee(&call_expr.callee),
+            arguments: Expression::SequenceExpression(Box::new(SequenceExpression {
+                node: call_expr.node,
+                expressions,
+            })),
+            named_arguments: vec![],
+        },
+    ))))
+}
+
+/// `math.<name>` for the called function, so the formula still finds it outside of `math`
+fn math_callee(callee: &Expression) -> Expression {
+    let id = match callee {
+        Expression::Identifier(id) => id,
+        Expression::MemberExpression(member_expression) if !member_expression.is_expr => {
+            return math_callee(&member_expression.property);
+        }
+        _ => return callee.clone(),
+    };
+
+    Expression::MemberExpression(Box::new(MemberExpression {
+        node: id.node,
+        object: Expression::Identifier(Identifier {
+            node: id.node,
+            name: "math".to_owned(),
+        }),
+        property: callee.clone(),
+        is_expr: false,
+    }))
+}
+
+fn get_real(interpreter: &mut Interpreter, call_expr: &CallExpression, value: &Value) -> f64 {
+    match value.as_f64() {
+        Some(n) => n,
+        None => {
+            interpreter.report(
+                "type-mismatch",
+                &format!("Expected a real number, got `{value}`"),
+                call_expr.node.start,
+                call_expr.node.end,
+            );
+            f64::NAN
+        }
+    }
+}
+
+fn get_complex(
+    interpreter: &mut Interpreter,
+    call_expr: &CallExpression,
+    value: &Value,
+) -> Complex64 {
+    match value.to_complex() {
+        Some(c) => c,
+        None => {
+            interpreter.report(
+                "type-mismatch",
+                &format!("Expected a number, got `{value}`"),
+                call_expr.node.start,
+                call_expr.node.end,
+            );
+            Complex64::new(f64::NAN, f64::NAN)
+        }
+    }
+}
@@ -5,9 +5,16 @@ use symboscript_types::{
 
 use super::Interpreter;
 
+pub mod algebra;
 pub mod conversions;
+pub mod error;
+pub mod generator;
 pub mod hashmap;
 pub mod io;
+pub mod math;
+pub mod range;
+pub mod sequence;
+pub mod time;
 
 mod lang;
 
@@ -27,14 +34,20 @@ pub fn run_function(
     interpreter: &mut Interpreter,
     call_expr: &CallExpression,
     native_function: &NativeFunction,
-    args: &Vec<Value>,
+    args: &[Value],
 ) -> Value {
     match native_function {
-        NativeFunction::IOPrintln => io::println(&args),
-        NativeFunction::IOPrint => io::print(&args),
+        NativeFunction::IOPrintln => io::println(args),
+        NativeFunction::IOPrint => io::print(args),
 
         NativeFunction::ToString => return conversions::to_string(interpreter, call_expr, args),
         NativeFunction::IsError => return conversions::is_err(interpreter, call_expr, args),
+        NativeFunction::ToInt => return conversions::to_int(interpreter, call_expr, args),
+        NativeFunction::ToFloat => return conversions::to_float(interpreter, call_expr, args),
+        NativeFunction::ToRational => {
+            return conversions::to_rational(interpreter, call_expr, args)
+        }
+        NativeFunction::ToComplex => return conversions::to_complex(interpreter, call_expr, args),
 
         NativeFunction::HMNew => return hashmap::new(interpreter, call_expr, args),
 
@@ -46,6 +59,78 @@ pub fn run_function(
         NativeFunction::HMKeys => return hashmap::keys(interpreter, call_expr, args),
         NativeFunction::HMValues => return hashmap::values(interpreter, call_expr, args),
         NativeFunction::HMClear => hashmap::clear(interpreter, call_expr, args),
+
+        NativeFunction::RangeNew => return range::new(interpreter, call_expr, args),
+        NativeFunction::RangeLen => return range::len(interpreter, call_expr, args),
+        NativeFunction::RangeContains => return range::contains(interpreter, call_expr, args),
+        NativeFunction::RangeStep => return range::step(interpreter, call_expr, args),
+        NativeFunction::RangeToSequence => return range::to_sequence(interpreter, call_expr, args),
+
+        NativeFunction::GenNext => return generator::next(interpreter, call_expr, args),
+        NativeFunction::GenDone => return generator::done(interpreter, call_expr, args),
+        NativeFunction::GenToSequence => {
+            return generator::to_sequence(interpreter, call_expr, args)
+        }
+
+        NativeFunction::ErrNew => return error::new(interpreter, call_expr, args),
+        NativeFunction::ErrMessage => return error::message(interpreter, call_expr, args),
+        NativeFunction::ErrKind => return error::kind(interpreter, call_expr, args),
+        NativeFunction::ErrPayload => return error::payload(interpreter, call_expr, args),
+        NativeFunction::ErrSpan => return error::span(interpreter, call_expr, args),
+        NativeFunction::ErrRethrow => error::rethrow(interpreter, call_expr, args),
+
+        NativeFunction::AlgSimplify => return algebra::simplify(interpreter, call_expr, args),
+        NativeFunction::AlgExpand => return algebra::expand(interpreter, call_expr, args),
+        NativeFunction::AlgSubstitute => return algebra::substitute(interpreter, call_expr, args),
+        NativeFunction::AlgEval => return algebra::eval(interpreter, call_expr, args),
+        NativeFunction::AlgDiff => return algebra::diff(interpreter, call_expr, args),
+
+        NativeFunction::MathSqrt => return math::sqrt(interpreter, call_expr, args),
+        NativeFunction::MathCbrt => return math::cbrt(interpreter, call_expr, args),
+        NativeFunction::MathExp => return math::exp(interpreter, call_expr, args),
+        NativeFunction::MathLn => return math::ln(interpreter, call_expr, args),
+        NativeFunction::MathLog => return math::log(interpreter, call_expr, args),
+        NativeFunction::MathLog2 => return math::log2(interpreter, call_expr, args),
+        NativeFunction::MathLog10 => return math::log10(interpreter, call_expr, args),
+        NativeFunction::MathSin => return math::sin(interpreter, call_expr, args),
+        NativeFunction::MathCos => return math::cos(interpreter, call_expr, args),
+        NativeFunction::MathTan => return math::tan(interpreter, call_expr, args),
+        NativeFunction::MathAsin => return math::asin(interpreter, call_expr, args),
+        NativeFunction::MathAcos => return math::acos(interpreter, call_expr, args),
+        NativeFunction::MathAtan => return math::atan(interpreter, call_expr, args),
+        NativeFunction::MathAtan2 => return math::atan2(interpreter, call_expr, args),
+        NativeFunction::MathSinh => return math::sinh(interpreter, call_expr, args),
+        NativeFunction::MathCosh => return math::cosh(interpreter, call_expr, args),
+        NativeFunction::MathTanh => return math::tanh(interpreter, call_expr, args),
+        NativeFunction::MathAbs => return math::abs(interpreter, call_expr, args),
+        NativeFunction::MathFloor => return math::floor(interpreter, call_expr, args),
+        NativeFunction::MathCeil => return math::ceil(interpreter, call_expr, args),
+        NativeFunction::MathRound => return math::round(interpreter, call_expr, args),
+        NativeFunction::MathTrunc => return math::trunc(interpreter, call_expr, args),
+        NativeFunction::MathSign => return math::sign(interpreter, call_expr, args),
+        NativeFunction::MathMin => return math::min(interpreter, call_expr, args),
+        NativeFunction::MathMax => return math::max(interpreter, call_expr, args),
+        NativeFunction::MathHypot => return math::hypot(interpreter, call_expr, args),
+        NativeFunction::MathRandom => return math::random(interpreter, call_expr, args),
+        NativeFunction::MathRandomInt => return math::random_int(interpreter, call_expr, args),
+        NativeFunction::MathRe => return math::re(interpreter, call_expr, args),
+        NativeFunction::MathIm => return math::im(interpreter, call_expr, args),
+        NativeFunction::MathConj => return math::conj(interpreter, call_expr, args),
+        NativeFunction::MathArg => return math::arg(interpreter, call_expr, args),
+
+        NativeFunction::Sleep => return time::sleep(interpreter, call_expr, args),
+
+        NativeFunction::SeqLen => return sequence::len(interpreter, call_expr, args),
+        NativeFunction::SeqPush => sequence::push(interpreter, call_expr, args),
+        NativeFunction::SeqPop => return sequence::pop(interpreter, call_expr, args),
+        NativeFunction::SeqMap => return sequence::map(interpreter, call_expr, args),
+        NativeFunction::SeqFilter => return sequence::filter(interpreter, call_expr, args),
+        NativeFunction::SeqReduce => return sequence::reduce(interpreter, call_expr, args),
+        NativeFunction::SeqSlice => return sequence::slice(interpreter, call_expr, args),
+        NativeFunction::SeqJoin => return sequence::join(interpreter, call_expr, args),
+        NativeFunction::SeqSort => return sequence::sort(interpreter, call_expr, args),
+        NativeFunction::SeqReverse => return sequence::reverse(interpreter, call_expr, args),
+        NativeFunction::SeqContains => return sequence::contains(interpreter, call_expr, args),
     }
     Value::None
 }
@@ -57,12 +142,58 @@ pub fn inject(interpreter: &mut Interpreter) {
 
     // ----------------- Std conversions --------------------------------
 
-    for name in ["&number", "&bool", "&str", "&sequence", "&ast", "&err"] {
+    for name in [
+        "&none",
+        "&number",
+        "&bool",
+        "&str",
+        "&sequence",
+        "&range",
+        "&generator",
+        "&future",
+        "&ast",
+        "&err",
+    ] {
         let scope = interpreter.start_declaration_of_named_scope(name);
         conversions::inject_methods(interpreter.get_curr_scope_values_mut());
+
+        match name {
+            "&sequence" => sequence::inject(interpreter.get_curr_scope_values_mut()),
+            "&range" => range::inject_methods(interpreter.get_curr_scope_values_mut()),
+            "&generator" => generator::inject(interpreter.get_curr_scope_values_mut()),
+            "&err" => error::inject_methods(interpreter.get_curr_scope_values_mut()),
+            _ => {}
+        }
+
         interpreter.end_declaration_of_named_scope(&scope);
     }
 
+    // ----------------- Numbers ----------------------------------------
+
+    conversions::inject(interpreter.get_curr_scope_values_mut());
+
+    // ----------------- Range ------------------------------------------
+
+    range::inject(interpreter.get_curr_scope_values_mut());
+
+    // ----------------- Error ------------------------------------------
+
+    error::inject(interpreter.get_curr_scope_values_mut());
+
+    // ----------------- Algebra ----------------------------------------
+
+    algebra::inject(interpreter.get_curr_scope_values_mut());
+
+    // ----------------- Async ------------------------------------------
+
+    time::inject(interpreter.get_curr_scope_values_mut());
+
+    // ----------------- Math -------------------------------------------
+
+    let scope = interpreter.start_declaration_of_named_scope("math");
+    math::inject(interpreter.get_curr_scope_values_mut());
+    interpreter.end_declaration_of_named_scope(&scope);
+
     // ----------------- Hashmap ----------------------------------------
 
     let scope = interpreter.start_declaration_of_named_scope("hashmap");
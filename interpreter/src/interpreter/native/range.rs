@@ -0,0 +1,143 @@
+use symboscript_types::{
+    interpreter::{NativeFunction, RangeValue, Scope, Value},
+    parser::CallExpression,
+};
+
+use crate::{expect_args, expect_args_range};
+
+use super::Interpreter;
+
+/// range[start, end, step?], `end` is not included
+pub fn new(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &[Value]) -> Value {
+    expect_args_range!(2, 3, interpreter, call_expr, args);
+
+    let start = number(interpreter, call_expr, &args[0]);
+    let end = number(interpreter, call_expr, &args[1]);
+
+    let mut range = RangeValue::new(start, end, false);
+
+    if let Some(step) = args.get(2) {
+        range.step = step_value(interpreter, call_expr, step);
+    }
+
+    Value::Range(range)
+}
+
+pub fn len(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &[Value]) -> Value {
+    expect_args!(0, interpreter, call_expr, args);
+
+    Value::Integer(get_range(interpreter).len().into())
+}
+
+pub fn contains(
+    interpreter: &mut Interpreter,
+    call_expr: &CallExpression,
+    args: &[Value],
+) -> Value {
+    expect_args!(1, interpreter, call_expr, args);
+
+    Value::Bool(match args[0].as_f64() {
+        Some(n) => get_range(interpreter).contains(n),
+        None => false,
+    })
+}
+
+/// step[n], keeps the direction of the range
+pub fn step(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &[Value]) -> Value {
+    expect_args!(1, interpreter, call_expr, args);
+
+    let mut range = get_range(interpreter);
+    let step = step_value(interpreter, call_expr, &args[0]);
+
+    range.step = step.abs() * range.step.signum();
+
+    Value::Range(range)
+}
+
+pub fn to_sequence(
+    interpreter: &mut Interpreter,
+    call_expr: &CallExpression,
+    args: &[Value],
+) -> Value {
+    expect_args!(0, interpreter, call_expr, args);
+
+    Value::Sequence(get_range(interpreter).iter().map(Value::integral).collect())
+}
+
+pub fn inject(scope: &mut Scope) {
+    scope.insert(
+        "range".to_owned(),
+        Value::NativeFunction(NativeFunction::RangeNew),
+    );
+}
+
+pub fn inject_methods(scope: &mut Scope) {
+    scope.insert(
+        "len".to_owned(),
+        Value::NativeFunction(NativeFunction::RangeLen),
+    );
+
+    scope.insert(
+        "contains".to_owned(),
+        Value::NativeFunction(NativeFunction::RangeContains),
+    );
+
+    scope.insert(
+        "step".to_owned(),
+        Value::NativeFunction(NativeFunction::RangeStep),
+    );
+
+    scope.insert(
+        "to_sequence".to_owned(),
+        Value::NativeFunction(NativeFunction::RangeToSequence),
+    );
+}
+
+fn step_value(interpreter: &mut Interpreter, call_expr: &CallExpression, value: &Value) -> f64 {
+    let step = number(interpreter, call_expr, value);
+
+    if step == 0.0 {
+        interpreter.report(
+            "out-of-range",
+            "Range step can't be zero",
+            call_expr.node.start,
+            call_expr.node.end,
+        );
+        return 1.0;
+    }
+
+    step
+}
+
+fn number(interpreter: &mut Interpreter, call_expr: &CallExpression, value: &Value) -> f64 {
+    match value.as_f64() {
+        Some(n) if n.is_finite() => n,
+        Some(_) => {
+            interpreter.report(
+                "out-of-range",
+                &format!("Range bounds must be finite, got `{value}`"),
+                call_expr.node.start,
+                call_expr.node.end,
+            );
+            f64::NAN
+        }
+        None => {
+            let got = value;
+
+            interpreter.report(
+                "type-mismatch",
+                &format!("Range bounds must be numbers, got `{got}`"),
+                call_expr.node.start,
+                call_expr.node.end,
+            );
+            f64::NAN
+        }
+    }
+}
+
+fn get_range(interpreter: &mut Interpreter) -> RangeValue {
+    match interpreter.get_cur_value("$value") {
+        Value::Range(range) => range,
+        _ => unreachable!("Range methods are only called on ranges"),
+    }
+}
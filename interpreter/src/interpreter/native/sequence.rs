@@ -0,0 +1,315 @@
+use std::cmp::Ordering;
+
+use num_traits::ToPrimitive;
+use symboscript_types::{
+    interpreter::{NativeFunction, Scope, Value},
+    parser::CallExpression,
+};
+
+use crate::{expect_args, expect_args_range};
+
+use super::Interpreter;
+
+pub fn len(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &[Value]) -> Value {
+    expect_args!(0, interpreter, call_expr, args);
+
+    Value::Integer(get_seq(interpreter).len().into())
+}
+
+pub fn push(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &[Value]) {
+    expect_args!(1, interpreter, call_expr, args);
+
+    let mut seq = get_seq(interpreter);
+    seq.push(args[0].clone());
+
+    set_seq(interpreter, seq);
+}
+
+pub fn pop(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &[Value]) -> Value {
+    expect_args!(0, interpreter, call_expr, args);
+
+    let mut seq = get_seq(interpreter);
+    let value = seq.pop().unwrap_or(Value::None);
+
+    set_seq(interpreter, seq);
+
+    value
+}
+
+pub fn map(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &[Value]) -> Value {
+    expect_args!(1, interpreter, call_expr, args);
+
+    Value::Sequence(
+        get_seq(interpreter)
+            .into_iter()
+            .map(|item| interpreter.call_value(args[0].clone(), &[item], call_expr))
+            .collect(),
+    )
+}
+
+pub fn filter(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &[Value]) -> Value {
+    expect_args!(1, interpreter, call_expr, args);
+
+    Value::Sequence(
+        get_seq(interpreter)
+            .into_iter()
+            .filter(|item| {
+                interpreter
+                    .call_value(args[0].clone(), std::slice::from_ref(item), call_expr)
+                    .as_bool()
+            })
+            .collect(),
+    )
+}
+
+/// reduce[fn[acc, item], initial?], without initial value the first item is used
+pub fn reduce(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &[Value]) -> Value {
+    expect_args_range!(1, 2, interpreter, call_expr, args);
+
+    let mut seq = get_seq(interpreter).into_iter();
+
+    let initial = match args.get(1) {
+        Some(initial) => Some(initial.clone()),
+        None => seq.next(),
+    };
+
+    let initial = match initial {
+        Some(initial) => initial,
+        None => {
+            interpreter.report(
+                "empty-sequence",
+                "Reduce of empty sequence with no initial value",
+                call_expr.node.start,
+                call_expr.node.end,
+            );
+            return Value::None;
+        }
+    };
+
+    seq.fold(initial, |acc, item| {
+        interpreter.call_value(args[0].clone(), &[acc, item], call_expr)
+    })
+}
+
+/// slice[start, end?], negative bounds are counted from the end
+pub fn slice(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &[Value]) -> Value {
+    expect_args_range!(1, 2, interpreter, call_expr, args);
+
+    let seq = get_seq(interpreter);
+    let len = seq.len();
+
+    let start = bound(interpreter, call_expr, &args[0], len);
+    let end = match args.get(1) {
+        Some(end) => bound(interpreter, call_expr, end, len),
+        None => len,
+    };
+
+    if start >= end {
+        return Value::Sequence(vec![]);
+    }
+
+    Value::Sequence(seq[start..end].to_vec())
+}
+
+pub fn join(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &[Value]) -> Value {
+    expect_args_range!(0, 1, interpreter, call_expr, args);
+
+    let separator = match args.first() {
+        Some(separator) => separator.to_string(),
+        None => String::new(),
+    };
+
+    Value::Str(
+        get_seq(interpreter)
+            .iter()
+            .map(|item| item.to_string())
+            .collect::<Vec<String>>()
+            .join(&separator),
+    )
+}
+
+/// sort[compare?], `compare[a, b]` returns a negative number if `a` goes first
+pub fn sort(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &[Value]) -> Value {
+    expect_args_range!(0, 1, interpreter, call_expr, args);
+
+    let seq = get_seq(interpreter);
+
+    let seq = match args.first() {
+        Some(compare) => merge_sort(seq, &mut |a, b| {
+            let order = interpreter.call_value(compare.clone(), &[a.clone(), b.clone()], call_expr);
+
+            match order.as_f64() {
+                Some(n) => n.partial_cmp(&0.0).unwrap_or(Ordering::Equal),
+                None => Ordering::Equal,
+            }
+        }),
+        None => merge_sort(seq, &mut compare),
+    };
+
+    set_seq(interpreter, seq.clone());
+
+    Value::Sequence(seq)
+}
+
+pub fn reverse(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &[Value]) -> Value {
+    expect_args!(0, interpreter, call_expr, args);
+
+    let mut seq = get_seq(interpreter);
+    seq.reverse();
+
+    set_seq(interpreter, seq.clone());
+
+    Value::Sequence(seq)
+}
+
+pub fn contains(
+    interpreter: &mut Interpreter,
+    call_expr: &CallExpression,
+    args: &[Value],
+) -> Value {
+    expect_args!(1, interpreter, call_expr, args);
+
+    Value::Bool(
+        get_seq(interpreter)
+            .iter()
+            .any(|item| item.equal(&args[0]).as_bool()),
+    )
+}
+
+pub fn inject(scope: &mut Scope) {
+    scope.insert(
+        "len".to_owned(),
+        Value::NativeFunction(NativeFunction::SeqLen),
+    );
+
+    scope.insert(
+        "push".to_owned(),
+        Value::NativeFunction(NativeFunction::SeqPush),
+    );
+
+    scope.insert(
+        "pop".to_owned(),
+        Value::NativeFunction(NativeFunction::SeqPop),
+    );
+
+    scope.insert(
+        "map".to_owned(),
+        Value::NativeFunction(NativeFunction::SeqMap),
+    );
+
+    scope.insert(
+        "filter".to_owned(),
+        Value::NativeFunction(NativeFunction::SeqFilter),
+    );
+
+    scope.insert(
+        "reduce".to_owned(),
+        Value::NativeFunction(NativeFunction::SeqReduce),
+    );
+
+    scope.insert(
+        "slice".to_owned(),
+        Value::NativeFunction(NativeFunction::SeqSlice),
+    );
+
+    scope.insert(
+        "join".to_owned(),
+        Value::NativeFunction(NativeFunction::SeqJoin),
+    );
+
+    scope.insert(
+        "sort".to_owned(),
+        Value::NativeFunction(NativeFunction::SeqSort),
+    );
+
+    scope.insert(
+        "reverse".to_owned(),
+        Value::NativeFunction(NativeFunction::SeqReverse),
+    );
+
+    scope.insert(
+        "contains".to_owned(),
+        Value::NativeFunction(NativeFunction::SeqContains),
+    );
+}
+
+/// Natural order: numbers, strings and bools are compared with their own kind
+fn compare(a: &Value, b: &Value) -> Ordering {
+    match (a, b) {
+        (n1, n2) if n1.is_number() && n2.is_number() => n1.compare(n2).unwrap_or(Ordering::Equal),
+        (Value::Str(s1), Value::Str(s2)) => s1.cmp(s2),
+        (Value::Bool(b1), Value::Bool(b2)) => b1.cmp(b2),
+        _ => Ordering::Equal,
+    }
+}
+
+/// Stable sort, unlike `sort_by` it doesn't panic if the comparator is not a total order
+fn merge_sort(
+    mut seq: Vec<Value>,
+    compare: &mut impl FnMut(&Value, &Value) -> Ordering,
+) -> Vec<Value> {
+    if seq.len() < 2 {
+        return seq;
+    }
+
+    let right = seq.split_off(seq.len() / 2);
+    let mut left = merge_sort(seq, compare).into_iter().peekable();
+    let mut right = merge_sort(right, compare).into_iter().peekable();
+
+    let mut merged = Vec::with_capacity(left.len() + right.len());
+
+    while let (Some(a), Some(b)) = (left.peek(), right.peek()) {
+        // Equal items keep their order
+        let next = if compare(b, a) == Ordering::Less {
+            right.next()
+        } else {
+            left.next()
+        };
+        merged.extend(next);
+    }
+
+    merged.extend(left);
+    merged.extend(right);
+
+    merged
+}
+
+/// Clamps a slice bound into `0..=len`
+fn bound(
+    interpreter: &mut Interpreter,
+    call_expr: &CallExpression,
+    value: &Value,
+    len: usize,
+) -> usize {
+    match value.to_integer().and_then(|n| n.to_isize()) {
+        Some(n) => {
+            let n = if n < 0 { len as isize + n } else { n };
+
+            n.clamp(0, len as isize) as usize
+        }
+        None => {
+            let got = value;
+
+            interpreter.report(
+                "type-mismatch",
+                &format!("Slice bound must be an integer, got `{got}`"),
+                call_expr.node.start,
+                call_expr.node.end,
+            );
+            0
+        }
+    }
+}
+
+fn get_seq(interpreter: &mut Interpreter) -> Vec<Value> {
+    match interpreter.get_cur_value("$value") {
+        Value::Sequence(seq) => seq,
+        _ => unreachable!("Sequence methods are only called on sequences"),
+    }
+}
+
+/// Changes the receiver, it is written back to the variable the method is called on
+fn set_seq(interpreter: &mut Interpreter, seq: Vec<Value>) {
+    interpreter.set_native_value("sequence", Value::Sequence(seq));
+    interpreter.receiver_changed = true;
+}
@@ -0,0 +1,46 @@
+use symboscript_types::{
+    interpreter::{NativeFunction, Scope, Value},
+    parser::CallExpression,
+};
+
+use crate::expect_args;
+
+use super::Interpreter;
+
+/// sleep[ms], returns a future resolved after `ms` milliseconds
+pub fn sleep(interpreter: &mut Interpreter, call_expr: &CallExpression, args: &[Value]) -> Value {
+    expect_args!(1, interpreter, call_expr, args);
+
+    match args[0].as_f64() {
+        Some(ms) => match interpreter.new_sleep(ms) {
+            Some(future) => future,
+            None => {
+                interpreter.report(
+                    "out-of-range",
+                    "Sleep duration out of range",
+                    call_expr.node.start,
+                    call_expr.node.end,
+                );
+                Value::None
+            }
+        },
+        None => {
+            let got = &args[0];
+
+            interpreter.report(
+                "type-mismatch",
+                &format!("Sleep duration must be a number, got `{got}`"),
+                call_expr.node.start,
+                call_expr.node.end,
+            );
+            Value::None
+        }
+    }
+}
+
+pub fn inject(scope: &mut Scope) {
+    scope.insert(
+        "sleep".to_owned(),
+        Value::NativeFunction(NativeFunction::Sleep),
+    );
+}
@@ -0,0 +1,7 @@
+mod interpreter;
+
+pub use interpreter::Interpreter;
+pub use symboscript_types::interpreter::RuntimeError;
+
+#[cfg(test)]
+mod tests;
@@ -1,13 +1,16 @@
 use clap::Parser;
 use std::fs::OpenOptions;
+use symboscript_utils::{
+    diagnostic::{report_diagnostics, runtime_diagnostics, MessageFormat},
+    report_parse_errors,
+};
 
 // use symboscript_optimizer as optimizer;
 use symboscript_parser as parser;
 
-mod interpreter;
 mod repl;
 
-use interpreter::Interpreter;
+use symboscript_interpreter::Interpreter;
 
 #[derive(Parser, Debug)]
 #[clap(author, version, about, long_about = None)]
@@ -19,6 +22,10 @@ struct Args {
     /// TODO: implement
     #[clap(short, long)]
     debug: bool,
+
+    /// Format of error messages: human, json or sarif
+    #[clap(long, default_value = "human")]
+    message_format: MessageFormat,
 }
 
 fn main() {
@@ -31,12 +38,17 @@ fn main() {
 
             let mut parser = parser::Parser::new(&path, text);
 
-            let ast = parser.parse();
+            let ast = match parser.parse() {
+                Ok(ast) => ast,
+                Err(errors) => report_parse_errors(&path, text, &errors, args.message_format),
+            };
             // let ast = optimizer::optimize(&ast);
 
             let mut interpreter = Interpreter::new(&path, text, false);
 
-            interpreter.run(ast);
+            if let Err(error) = interpreter.run(ast) {
+                report_diagnostics(&runtime_diagnostics(&error), args.message_format);
+            }
         }
 
         None => {
@@ -1,5 +1,9 @@
 use crate::parser::Parser;
 use crate::Interpreter;
+use symboscript_utils::{
+    diagnostic::{print_diagnostics, runtime_diagnostics, MessageFormat},
+    print_parse_errors,
+};
 
 use rustyline::error::ReadlineError;
 use rustyline::Result;
@@ -49,11 +53,21 @@ pub fn start() -> Result<()> {
                 }
 
                 let curr_src = format!("repl/{k}/");
-                let ast = Parser::new(&curr_src, &line).parse();
+                // Syntax errors don't end the session
+                let ast = match Parser::new(&curr_src, &line).parse() {
+                    Ok(ast) => ast,
+                    Err(errors) => {
+                        print_parse_errors(&curr_src, &line, &errors);
+                        continue;
+                    }
+                };
 
                 interpreter.append_to_current_source(line);
 
-                interpreter.eval_ast(ast);
+                // Runtime errors don't end the session either
+                if let Err(error) = interpreter.eval_ast(ast) {
+                    print_diagnostics(&runtime_diagnostics(&error), MessageFormat::Human);
+                }
             }
 
             Err(ReadlineError::Interrupted) => {
@@ -0,0 +1,1308 @@
+#[macro_use]
+mod utils {
+    macro_rules! assert_interpreter {
+        // Before the other arms, a list would be parsed as an index of `traceback`
+        ($str: expr, traceback $frames: expr) => {{
+            use crate::interpreter::Interpreter;
+            use symboscript_parser::Parser;
+
+            let test_str = $str;
+            let ast = Parser::new("test", test_str).parse().unwrap();
+
+            let mut interpreter = Interpreter::new("test", test_str, false);
+
+            match interpreter.run(ast) {
+                Err(error) => assert_eq!(
+                    error
+                        .traceback
+                        .iter()
+                        .map(|frame| format!("{}:{}", frame.function, frame.line))
+                        .collect::<Vec<_>>(),
+                    $frames
+                ),
+                result => panic!("Expected runtime error, got {:?}", result),
+            }
+        }};
+
+        ($str: expr, $value_str: expr) => {{
+            use crate::interpreter::Interpreter;
+            use symboscript_parser::Parser;
+            use symboscript_types::interpreter::ControlFlow;
+
+            let test_str = $str;
+            let ast = Parser::new("test", test_str).parse().unwrap();
+
+            let mut interpreter = Interpreter::new("test", test_str, false);
+            interpreter.initialize();
+
+            match interpreter.eval_ast(ast) {
+                Ok(ControlFlow::Return(value)) => assert_eq!(format!("{}", value), $value_str),
+                control => panic!("Expected top-level return, got {:?}", control),
+            }
+        }};
+
+        ($str: expr, throws $value_str: expr) => {{
+            use crate::interpreter::Interpreter;
+            use symboscript_parser::Parser;
+            use symboscript_types::interpreter::ControlFlow;
+
+            let test_str = $str;
+            let ast = Parser::new("test", test_str).parse().unwrap();
+
+            let mut interpreter = Interpreter::new("test", test_str, false);
+            interpreter.initialize();
+
+            match interpreter.eval_ast(ast) {
+                Ok(ControlFlow::Throw(value)) => assert_eq!(format!("{}", value), $value_str),
+                control => panic!("Expected top-level throw, got {:?}", control),
+            }
+        }};
+
+        ($str: expr, fails $message: expr) => {{
+            use crate::interpreter::Interpreter;
+            use symboscript_parser::Parser;
+
+            let test_str = $str;
+            let ast = Parser::new("test", test_str).parse().unwrap();
+
+            let mut interpreter = Interpreter::new("test", test_str, false);
+
+            match interpreter.run(ast) {
+                Err(error) => assert_eq!(error.message, $message),
+                result => panic!("Expected runtime error, got {:?}", result),
+            }
+        }};
+
+        ($str: expr, fails with code $code: expr) => {{
+            use crate::interpreter::Interpreter;
+            use symboscript_parser::Parser;
+
+            let test_str = $str;
+            let ast = Parser::new("test", test_str).parse().unwrap();
+
+            let mut interpreter = Interpreter::new("test", test_str, false);
+
+            match interpreter.run(ast) {
+                Err(error) => assert_eq!(error.code, $code),
+                result => panic!("Expected runtime error, got {:?}", result),
+            }
+        }};
+    }
+}
+
+pub mod loop_tests {
+    #[test]
+    fn for_loop() {
+        assert_interpreter!(
+            "let a = 0; for (let i = 0; i < 10; ++i) mut a += i; return a;",
+            "45"
+        );
+        assert_interpreter!(
+            "let a = 0; for (let i = 10; i > 0; --i) { mut a += 1; } return a;",
+            "10"
+        );
+    }
+
+    #[test]
+    fn for_loop_scope() {
+        assert_interpreter!(
+            "let i = 100; for (let i = 0; i < 3; ++i) { let k = i; } return i;",
+            "100"
+        );
+        assert_interpreter!(
+            "let a = 0; for (let i = 0; i < 3; ++i) { let k = 1; mut a += k; } return a;",
+            "3"
+        );
+    }
+
+    #[test]
+    fn for_loop_iteration_scope() {
+        assert_interpreter!(
+            "let fs = []; for (let i = 0; i < 3; ++i) fs.push[fn[] i]; return [fs[0][], fs[1][], fs[2][]];",
+            "[0, 1, 2]"
+        );
+        assert_interpreter!(
+            "let a = 0; for (let i = 0; i < 10; ++i) { mut i += 1; mut a += 1; } return a;",
+            "5"
+        );
+    }
+
+    #[test]
+    fn for_loop_controls() {
+        assert_interpreter!(
+            "let a = 0; for (let i = 0; i < 10; ++i) { if (i == 2) continue if (i == 5) break mut a += i; } return a;",
+            "8"
+        );
+    }
+
+    #[test]
+    fn nested_for_loops() {
+        assert_interpreter!(
+            "let a = 0;
+            for (let i = 0; i < 4; ++i) {
+                for (let j = 0; j < 4; ++j) {
+                    if (j > i) break
+                    mut a += 1;
+                }
+            }
+            return a;",
+            "10"
+        );
+    }
+
+    #[test]
+    fn return_from_for_loop() {
+        assert_interpreter!(
+            "fn find[n] {
+                for (let i = 0; i < 100; ++i) {
+                    for (let j = 0; j < 100; ++j) {
+                        if (i * j == n) return i * 100 + j;
+                    }
+                }
+                return -1;
+            }
+            return find[12];",
+            "112"
+        );
+        assert_interpreter!(
+            "fn first[] { for (let i = 5; i < 10; ++i) return i; }
+            let a = first[];
+            return a + first[];",
+            "10"
+        );
+    }
+
+    #[test]
+    fn while_and_loop_return() {
+        assert_interpreter!(
+            "fn f[] { let i = 0; while (true) { ++i; if (i == 3) return i; } } return f[];",
+            "3"
+        );
+        assert_interpreter!(
+            "fn f[] { let i = 0; loop { ++i; if (i == 4) return i; } } return f[];",
+            "4"
+        );
+    }
+}
+
+pub mod for_in_tests {
+    #[test]
+    fn for_in_range() {
+        assert_interpreter!("let a = 0; for (let x in 1..4) mut a += x; return a;", "10");
+        assert_interpreter!("let a = 0; for (x in 1..4) mut a += x; return a;", "10");
+    }
+
+    #[test]
+    fn for_in_iteration_scope() {
+        assert_interpreter!(
+            "let fs = []; for (x in [1, 2, 3]) fs.push[fn[] x]; return [fs[0][], fs[1][], fs[2][]];",
+            "[1, 2, 3]"
+        );
+    }
+
+    #[test]
+    fn for_in_hashmap() {
+        assert_interpreter!(
+            "let hm = hashmap.new[];
+            hm.set[\"a\", 1];
+            hm.set[\"b\", 2];
+            let keys = \"\";
+            for (k in hm) mut keys += k;
+            return keys == \"ab\" || keys == \"ba\";",
+            "true"
+        );
+        assert_interpreter!(
+            "let hm = hashmap.new[];
+            hm.set[\"a\", 1];
+            hm.set[\"b\", 2];
+            let a = 0;
+            for (v of hm) mut a += v;
+            return a;",
+            "3"
+        );
+    }
+
+    #[test]
+    fn for_in_controls() {
+        assert_interpreter!(
+            "let a = 0;
+            for (x in 1..10) {
+                if (x == 2) continue
+                if (x == 5) break
+                mut a += x;
+            }
+            return a;",
+            "8"
+        );
+        assert_interpreter!(
+            "fn f[] { for (x in 1..10) for (y in 1..10) if (x * y == 6) return x; } return f[];",
+            "1"
+        );
+    }
+}
+
+pub mod expr_tests {
+    #[test]
+    fn ternary_op() {
+        assert_interpreter!("return true ? 1 : 2;", "1");
+        assert_interpreter!("return false ? 1 : 2;", "2");
+        assert_interpreter!("let a = 0; return a ? 1 : 2;", "2");
+
+        assert_interpreter!("return false ? 1 : true ? 2 : 3;", "2");
+        assert_interpreter!("return false ? 1 : false ? 2 : 3;", "3");
+        assert_interpreter!("return false ? 1 : false ? 2 : true ? 3 : 4;", "3");
+
+        assert_interpreter!("return (true ? false : true) ? 1 : 2;", "2");
+    }
+
+    #[test]
+    fn ternary_op_is_lazy() {
+        assert_interpreter!(
+            "let a = 0;
+            fn inc[] { mut a += 1; return a; }
+            let b = true ? 10 : inc[];
+            let c = false ? inc[] : 20;
+            return a + b + c;",
+            "30"
+        );
+    }
+}
+
+pub mod sequence_tests {
+    #[test]
+    fn sequence_literal() {
+        assert_interpreter!("return [];", "[]");
+        assert_interpreter!("let a = 2; return [1, a, a + 1];", "[1, 2, 3]");
+        assert_interpreter!("return [[1], [2, 3]];", "[[1], [2, 3]]");
+        assert_interpreter!("return [1, 2] == [1, 2];", "true");
+    }
+
+    #[test]
+    fn sequence_index() {
+        assert_interpreter!("let xs = [1, 2, 3]; return xs[0];", "1");
+        assert_interpreter!("let xs = [1, 2, 3]; return xs[-1];", "3");
+        assert_interpreter!(
+            "let xs = [[1, 2], [3, 4]]; let row = xs[1]; return row[0];",
+            "3"
+        );
+    }
+
+    #[test]
+    fn sequence_index_assign() {
+        assert_interpreter!("let xs = [1, 2, 3]; mut xs[0] = 5; return xs;", "[5, 2, 3]");
+        assert_interpreter!(
+            "let xs = [1, 2, 3]; mut xs[-1] += 5; return xs;",
+            "[1, 2, 8]"
+        );
+        assert_interpreter!(
+            "let xs = [[1, 2], [3, 4]]; mut xs[1][0] = 0; return xs;",
+            "[[1, 2], [0, 4]]"
+        );
+    }
+
+    #[test]
+    fn sequence_mutating_methods() {
+        assert_interpreter!("let xs = [1]; xs.push[2]; return xs;", "[1, 2]");
+        assert_interpreter!(
+            "let xs = [1, 2]; let a = xs.pop[]; return [a, xs];",
+            "[2, [1]]"
+        );
+        assert_interpreter!("let xs = [3, 1, 2]; xs.sort[]; return xs;", "[1, 2, 3]");
+        assert_interpreter!("let xs = [1, 2, 3]; xs.reverse[]; return xs;", "[3, 2, 1]");
+        assert_interpreter!(
+            "scope obj { let xs = [1]; } obj.xs.push[2]; return obj.xs;",
+            "[1, 2]"
+        );
+        assert_interpreter!(
+            "let m = [[1], [2]]; m[0].push[5]; m[-1].pop[]; return m;",
+            "[[1, 5], []]"
+        );
+        assert_interpreter!(
+            "let m = [[[3, 1]]]; m[0][0].sort[]; return m;",
+            "[[[1, 3]]]"
+        );
+    }
+
+    #[test]
+    fn sequence_methods() {
+        assert_interpreter!("let xs = [1, 2, 3]; return xs.len[];", "3");
+        assert_interpreter!("let xs = [1, 2, 3]; return xs.slice[1];", "[2, 3]");
+        assert_interpreter!("let xs = [1, 2, 3]; return xs.slice[0, -1];", "[1, 2]");
+        assert_interpreter!("let xs = [1, 2, 3]; return xs.join[\", \"];", "1, 2, 3");
+        assert_interpreter!("let xs = [1, 2, 3]; return xs.contains[2];", "true");
+        assert_interpreter!("let xs = [1, 2, 3]; return xs.contains[4];", "false");
+        assert_interpreter!("let xs = [[1], [2]]; return xs.contains[[2]];", "true");
+    }
+
+    #[test]
+    fn sequence_callbacks() {
+        assert_interpreter!(
+            "fn double[x] return x * 2; let xs = [1, 2, 3]; return xs.map[double];",
+            "[2, 4, 6]"
+        );
+        assert_interpreter!(
+            "fn odd[x] return x % 2 == 1; let xs = [1, 2, 3]; return xs.filter[odd];",
+            "[1, 3]"
+        );
+        assert_interpreter!(
+            "fn add[a, b] return a + b; let xs = [1, 2, 3]; return [xs.reduce[add], xs.reduce[add, 10]];",
+            "[6, 16]"
+        );
+        assert_interpreter!(
+            "fn desc[a, b] return b - a; let xs = [1, 3, 2]; return xs.sort[desc];",
+            "[3, 2, 1]"
+        );
+    }
+
+    #[test]
+    fn sort_with_inconsistent_comparator() {
+        assert_interpreter!(
+            "let xs = []; for (x in 1..2000) xs.push[x];
+            let ys = xs.sort[fn[a, b] math.random[] - 0.5];
+            return [ys.len[], ys.reduce[fn[a, b] a + b]];",
+            "[2000, 2001000]"
+        );
+    }
+
+    #[test]
+    fn nested_sequence_callbacks() {
+        assert_interpreter!(
+            "fn add[a, b] return a + b;
+            fn total[row] return row.reduce[add];
+            let xs = [[1], [1, 2], [1, 2, 3]];
+            return xs.map[total];",
+            "[1, 3, 6]"
+        );
+        assert_interpreter!(
+            "let xs = [1, 2]; let ys = [1, 2, 3]; xs.push[ys.len[]]; return xs;",
+            "[1, 2, 3]"
+        );
+    }
+}
+
+pub mod range_tests {
+    #[test]
+    fn range_display() {
+        assert_interpreter!("return 1..5;", "1..5");
+        assert_interpreter!("return range[0, 10, 2];", "0..<10 by 2");
+        assert_interpreter!("let r = 10..0; return r.step[5];", "10..0 by -5");
+    }
+
+    #[test]
+    fn range_methods() {
+        assert_interpreter!("let r = 1..5; return r.len[];", "5");
+        assert_interpreter!("let r = range[1, 5]; return r.len[];", "4");
+        assert_interpreter!("let r = 5..1; return r.to_sequence[];", "[5, 4, 3, 2, 1]");
+        assert_interpreter!(
+            "let r = range[0, 10, 3]; return r.to_sequence[];",
+            "[0, 3, 6, 9]"
+        );
+        assert_interpreter!(
+            "let r = 0..10; return [r.contains[10], r.contains[2.5], r.contains[11]];",
+            "[true, false, false]"
+        );
+        assert_interpreter!("let r = range[0, 1, -1]; return r.len[];", "0");
+    }
+
+    #[test]
+    fn range_iteration() {
+        assert_interpreter!(
+            "let s = 0; for (let i in 1..100) mut s += i; return s;",
+            "5050"
+        );
+        assert_interpreter!(
+            "let xs = []; for (let i in range[3, 0]) xs.push[i]; return xs;",
+            "[3, 2, 1]"
+        );
+        assert_interpreter!("let r = 0..1000000000; return r[-1];", "1000000000");
+    }
+
+    #[test]
+    fn unbounded_ranges() {
+        assert_interpreter!(
+            "let r = 0..(1.0/0.0);",
+            fails "Range bounds must be finite, got `0` and `inf`"
+        );
+        assert_interpreter!(
+            "for (x in range[0, -1.0/0.0]) {}",
+            fails "Range bounds must be finite, got `-inf`"
+        );
+        assert_interpreter!(
+            "let r = 0..1e300; return [r.len[] > 0, r.contains[5], r[3]];",
+            "[true, true, 3]"
+        );
+    }
+}
+
+pub mod generator_tests {
+    #[test]
+    fn generator_next() {
+        assert_interpreter!(
+            "fn gen[] { yield 1; yield 2; }
+            let g = gen[];
+            return [g.next[], g.next[], g.next[]];",
+            "[1, 2, None]"
+        );
+        assert_interpreter!(
+            "fn gen[] { yield 1; }
+            let g = gen[];
+            let a = g.done[];
+            g.next[];
+            g.next[];
+            return [a, g.done[]];",
+            "[false, true]"
+        );
+        assert_interpreter!(
+            "fn nothing[] {}
+            fn gen[] { yield nothing[]; }
+            let g = gen[];
+            let a = [g.next[], g.done[]];
+            let b = [g.next[], g.done[]];
+            return [a, b];",
+            "[[None, false], [None, true]]"
+        );
+    }
+
+    #[test]
+    fn generator_keeps_state() {
+        assert_interpreter!(
+            "fn counter[from] { let i = from; loop { yield i; mut i += 1; } }
+            let g = counter[5];
+            g.next[];
+            g.next[];
+            return g.next[];",
+            "7"
+        );
+        assert_interpreter!(
+            "fn evens[n] {
+                for (let i = 0; i < n; ++i) {
+                    if (i % 2 == 0) { yield i; } else { yield -i; }
+                }
+            }
+            let g = evens[4];
+            return g.to_sequence[];",
+            "[0, -1, 2, -3]"
+        );
+        assert_interpreter!(
+            "fn pairs[xs] { for (let x in xs) { let y = x * 10; yield x; yield y; } }
+            let g = pairs[1..3];
+            return g.to_sequence[];",
+            "[1, 10, 2, 20, 3, 30]"
+        );
+    }
+
+    #[test]
+    fn generator_iteration() {
+        assert_interpreter!(
+            "fn squares[] { let i = 1; while (true) { yield i * i; mut i += 1; } }
+            let xs = [];
+            for (let x in squares[]) { if (x > 30) break xs.push[x]; }
+            return xs;",
+            "[1, 4, 9, 16, 25]"
+        );
+        assert_interpreter!(
+            "fn inner[] { yield 1; yield 2; }
+            fn outer[] { for (let x in inner[]) yield x + 1; yield 0; }
+            return outer[].to_sequence[];",
+            "[2, 3, 0]"
+        );
+    }
+
+    #[test]
+    fn independent_generators() {
+        assert_interpreter!(
+            "fn gen[] { yield 1; yield 2; }
+            let a = gen[];
+            let b = gen[];
+            a.next[];
+            return [a.next[], b.next[]];",
+            "[2, 1]"
+        );
+    }
+}
+
+pub mod async_tests {
+    #[test]
+    fn await_task() {
+        assert_interpreter!(
+            "async fn double[x] { await sleep[1]; return x * 2; } return await double[21];",
+            "42"
+        );
+        assert_interpreter!("let v = 5; return await v;", "5");
+        assert_interpreter!(
+            "async fn get[] return 1; let f = get[]; return [f, await f, await f];",
+            "[<future>, 1, 1]"
+        );
+    }
+
+    #[test]
+    fn tasks_interleave() {
+        assert_interpreter!(
+            "let log = [];
+            async fn worker[name, ms] { await sleep[ms]; log.push[name]; }
+            let a = worker[\"slow\", 20];
+            let b = worker[\"fast\", 5];
+            await a;
+            await b;
+            return log;",
+            "[fast, slow]"
+        );
+        assert_interpreter!(
+            "let log = [];
+            async fn step[name, n] {
+                for (let i = 0; i < n; ++i) { log.push[name + i]; await sleep[1]; }
+            }
+            let x = step[\"x\", 3];
+            let y = step[\"y\", 2];
+            await x;
+            await y;
+            return log;",
+            "[x0, y0, x1, y1, x2]"
+        );
+    }
+
+    #[test]
+    fn suspended_statement_is_not_repeated() {
+        assert_interpreter!(
+            "let calls = 0;
+            fn count[] { mut calls += 1; return calls; }
+            async fn value[] { await sleep[1]; return 10; }
+            async fn sum[] { return count[] + await value[]; }
+            let total = await sum[];
+            return [total, calls];",
+            "[11, 1]"
+        );
+        assert_interpreter!(
+            "async fn value[x] { await sleep[1]; return x; }
+            async fn pair[] { return [await value[1], await value[2]]; }
+            return await pair[];",
+            "[1, 2]"
+        );
+    }
+}
+
+pub mod try_tests {
+    #[test]
+    fn catch_thrown_value() {
+        assert_interpreter!(
+            "let r = 0; try { throw 5; mut r = 1; } catch e { mut r = e; } return r;",
+            "5"
+        );
+        assert_interpreter!(
+            "fn inner[] throw [1, 2];
+            fn outer[] { let v = inner[]; return 0; }
+            try { outer[]; } catch e { return e; }",
+            "[1, 2]"
+        );
+        assert_interpreter!(
+            "fn f[x] { if (x == 3) throw x; return x; }
+            let xs = [];
+            try { for (let i = 0; i < 5; ++i) xs.push[f[i]]; } catch { xs.push[-1]; }
+            return xs;",
+            "[0, 1, 2, -1]"
+        );
+    }
+
+    #[test]
+    fn finally() {
+        assert_interpreter!(
+            "let log = [];
+            try { log.push[1]; } finally { log.push[2]; }
+            try { try { throw 0; } finally { log.push[3]; } } catch { log.push[4]; }
+            return log;",
+            "[1, 2, 3, 4]"
+        );
+        assert_interpreter!(
+            "fn f[] { try { return 1; } finally { return 2; } } return f[];",
+            "2"
+        );
+        assert_interpreter!(
+            "fn f[] { try { throw 1; } catch e { throw e + 1; } } try { f[]; } catch e { return e; }",
+            "2"
+        );
+    }
+
+    #[test]
+    fn uncaught_throw() {
+        assert_interpreter!(
+            "fn f[] { try { throw 1; } finally { } } f[]; return 0;",
+            throws "1"
+        );
+        assert_interpreter!("let xs = [1]; for (let x in xs) throw x + 1;", throws "2");
+    }
+
+    #[test]
+    fn suspend_in_try() {
+        assert_interpreter!(
+            "fn gen[] { try { yield 1; throw 2; } catch e { yield e; } finally { yield 3; } }
+            return gen[].to_sequence[];",
+            "[1, 2, 3]"
+        );
+        assert_interpreter!(
+            "async fn fail[] { await sleep[1]; throw \"boom\"; }
+            async fn run[] { try { await fail[]; } catch e { return \"caught \" + e; } }
+            return await run[];",
+            "caught boom"
+        );
+    }
+
+    #[test]
+    fn sleep_out_of_range() {
+        assert_interpreter!(
+            "await sleep[1.0 / 0.0];",
+            fails "Sleep duration out of range"
+        );
+        assert_interpreter!(
+            "await sleep[1e300];",
+            fails "Sleep duration out of range"
+        );
+        assert_interpreter!("await sleep[-5]; return 1;", "1");
+    }
+
+    #[test]
+    fn unhandled_task_failure() {
+        assert_interpreter!(
+            "async fn fail[] { await sleep[1]; throw \"boom\"; } fail[];",
+            fails "Uncaught exception: boom"
+        );
+    }
+
+    #[test]
+    fn awaited_task_failure_is_handled() {
+        use crate::interpreter::Interpreter;
+        use symboscript_parser::Parser;
+
+        let source = "async fn fail[] throw 1;
+            async fn run[] { let f = fail[]; await sleep[5]; try { await f; } catch { } }
+            run[];";
+        let ast = Parser::new("test", source).parse().unwrap();
+
+        assert!(Interpreter::new("test", source, false).run(ast).is_ok());
+    }
+}
+
+pub mod error_tests {
+    #[test]
+    fn error_values() {
+        assert_interpreter!(
+            "let e = error[\"ZeroDivision\", \"Division by zero\"]; return e;",
+            "ZeroDivision: Division by zero"
+        );
+        assert_interpreter!(
+            "let e = error[\"NotFound\", \"No file\", \"a.txt\"];
+            return [e.kind[], e.message[], e.payload[], e.span[], e.is_err[]];",
+            "[NotFound, No file, a.txt, None, true]"
+        );
+    }
+
+    #[test]
+    fn none_methods() {
+        assert_interpreter!("fn f[] {} return f[].to_string[];", "None");
+        assert_interpreter!("let xs = [1]; return xs.push[2].is_err[];", "false");
+    }
+
+    #[test]
+    fn thrown_error_span() {
+        assert_interpreter!(
+            "try { throw error[\"A\", \"b\"]; } catch e { return e.span[]; }",
+            "[6, 28]"
+        );
+        assert_interpreter!(
+            "fn f[] throw error[\"A\", \"b\"];
+            try { try { f[]; } catch e { throw e; } } catch e { return e.span[]; }",
+            "[7, 29]"
+        );
+    }
+
+    #[test]
+    fn distinguish_errors() {
+        assert_interpreter!(
+            "fn div[a, b] { if (b == 0) throw error[\"ZeroDivision\", \"Division by zero\"]; return a / b; }
+            fn handle[b] {
+                try { return div[1, b]; }
+                catch e { if (e.kind[] == \"ZeroDivision\") return 0; e.rethrow[]; }
+            }
+            return handle[0];",
+            "0"
+        );
+        assert_interpreter!(
+            "fn f[] { try { throw error[\"Other\", \"x\"]; } catch e { e.rethrow[]; } }
+            f[];",
+            throws "Other: x"
+        );
+    }
+}
+
+pub mod runtime_error_tests {
+    #[test]
+    fn returned_errors() {
+        assert_interpreter!("return a;", fails "Variable `a` not found");
+        assert_interpreter!("let a = 1; a[];", fails "`a` is not a function");
+        assert_interpreter!("let a = 1; delete a;", fails "`delete` is not supported yet");
+        assert_interpreter!(
+            "fn f[a] return a; f[1, 2];",
+            fails "Expected at most 1 argument for `f[a]`, got 2"
+        );
+        assert_interpreter!(
+            "fn f[a, b] return a; f[1, 2, 3];",
+            fails "Expected at most 2 arguments for `f[a, b]`, got 3"
+        );
+        assert_interpreter!("math.sqrt[];", fails "Wrong number of arguments (expected 1)");
+    }
+
+    #[test]
+    fn error_codes() {
+        assert_interpreter!("return a;", fails with code "undefined-variable");
+        assert_interpreter!("let a = 1; a[];", fails with code "type-mismatch");
+        assert_interpreter!("math.sqrt[];", fails with code "invalid-arguments");
+        assert_interpreter!("let xs = [1]; return xs[5];", fails with code "index-error");
+        assert_interpreter!("throw 1;", fails with code "uncaught-exception");
+        assert_interpreter!("let a := a; return a;", fails with code "formula-cycle");
+    }
+
+    #[test]
+    fn errors_are_not_caught() {
+        assert_interpreter!(
+            "fn f[] { for (let i = 0; i < 3; ++i) { mut b = i; } } f[]; return 1;",
+            fails "Variable `b` not found"
+        );
+        assert_interpreter!(
+            "let a = 0; try { loop { mut a = c; } } catch e { return e; } finally { return a; }",
+            fails "Variable `c` not found"
+        );
+    }
+
+    #[test]
+    fn tracebacks() {
+        assert_interpreter!(
+            "fn fact[n] {
+                if (n == 0) return missing;
+                return n * fact[n - 1];
+            }
+            fact[2];",
+            traceback [
+                "<main>:5", "fact:3", "fact:3", "fact:2"
+            ]
+        );
+        assert_interpreter!(
+            "let f = fn[] { throw error[\"A\", \"b\"]; };
+            fn g[] { f[]; }
+            g[];",
+            traceback ["<main>:3", "g:2", "<anonymous>:1"]
+        );
+        assert_interpreter!("\nthrow 1;", traceback["<main>:2"]);
+    }
+
+    #[test]
+    fn import_reports_every_syntax_error() {
+        use crate::interpreter::Interpreter;
+        use std::fs;
+        use symboscript_parser::Parser;
+
+        let dir = std::env::temp_dir().join("symboscript_import_errors");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("broken.syms"), "let = 1;\nlet x = ;\n").unwrap();
+
+        let path = dir.join("main.syms").display().to_string();
+        let source = "import broken as b;";
+        let ast = Parser::new(&path, source).parse().unwrap();
+
+        let error = Interpreter::new(&path, source, false).run(ast).unwrap_err();
+        let lines = std::iter::once(&*error)
+            .chain(&error.related)
+            .map(|error| error.traceback.last().unwrap().line)
+            .collect::<Vec<_>>();
+
+        assert_eq!(lines, [1, 2]);
+    }
+
+    #[test]
+    fn error_labels() {
+        use crate::interpreter::Interpreter;
+        use symboscript_parser::Parser;
+        use symboscript_types::parser::Node;
+
+        let run = |source: &str| {
+            let ast = Parser::new("test", source).parse().unwrap();
+            Interpreter::new("test", source, false)
+                .run(ast)
+                .unwrap_err()
+        };
+
+        let error = run("let a := b + 1; let b := a * 2; return a;");
+        assert_eq!(
+            error.message,
+            "Formulas depend on each other: `a` -> `b` -> `a`"
+        );
+        assert_eq!(
+            error.labels,
+            [(Node::new(0, 15), "`a` is declared here".to_owned())]
+        );
+
+        let error = run("fn f[b] b; f[b = 1, b = 2];");
+        assert_eq!(error.node, Node::new(20, 25));
+        assert_eq!(
+            error.labels,
+            [(Node::new(13, 18), "first given here".to_owned())]
+        );
+
+        let error = run("fn f[b] b; f[1, b = 2];");
+        assert_eq!(error.notes, ["`b` is also given as positional argument 1"]);
+    }
+}
+
+pub mod closure_tests {
+    #[test]
+    fn captures_definition_scope() {
+        assert_interpreter!(
+            "fn make_adder[n] { fn add[x] return x + n; return add; }
+            let add2 = make_adder[2];
+            let add5 = make_adder[5];
+            return [add2[1], add5[1]];",
+            "[3, 6]"
+        );
+        assert_interpreter!(
+            "let x = 1;
+            fn get_x[] return x;
+            fn shadow[] { let x = 2; return get_x[]; }
+            return shadow[];",
+            "1"
+        );
+    }
+
+    #[test]
+    fn mutates_captured_variable() {
+        assert_interpreter!(
+            "fn counter[] { let count = 0; fn next[] { mut count += 1; return count; } return next; }
+            let a = counter[];
+            let b = counter[];
+            a[]; a[];
+            return [a[], b[]];",
+            "[3, 1]"
+        );
+    }
+
+    #[test]
+    fn captures_block_scope() {
+        assert_interpreter!(
+            "let fns = [];
+            for (let i = 0; i < 3; ++i) { let j = i * 10; fn get[] return j; fns.push[get]; }
+            let values = [];
+            for (get in fns) values.push[get[]];
+            return values;",
+            "[0, 10, 20]"
+        );
+    }
+
+    #[test]
+    fn generator_closure() {
+        assert_interpreter!(
+            "fn numbers[] { let step = 3; fn scale[x] return x * step; yield scale; yield 1; }
+            let gen = numbers[];
+            let scale = gen.next[];
+            gen.next[];
+            return scale[2];",
+            "6"
+        );
+    }
+
+    #[test]
+    fn scopes_are_freed() {
+        assert_interpreter!(
+            "let kept = [];
+            for (let i = 0; i < 1000; ++i) {
+                let f = fn[] i;
+                if (i % 250 == 0) kept.push[f];
+            }
+            return kept.map[fn[f] f[]];",
+            "[0, 250, 500, 750]"
+        );
+        assert_interpreter!(
+            "fn counter[] { let count = 0; fn next[] { mut count += 1; return count; } return next; }
+            let c = counter[];
+            for (let i = 0; i < 1000; ++i) { counter[]; c[]; }
+            return c[];",
+            "1001"
+        );
+        assert_interpreter!(
+            "fn gen[] { let n = 0; loop { mut n += 1; yield fn[] n; } }
+            let g = gen[];
+            g.next[];
+            for (let i = 0; i < 1000; ++i) { let other = gen[]; other.next[]; }
+            return [g.next[][], g.next[][]];",
+            "[2, 3]"
+        );
+    }
+
+    #[test]
+    fn anonymous_functions() {
+        assert_interpreter!(
+            "let values = [1, 2, 3];
+            let doubled = values.map[fn[x] x * 2];
+            return doubled.filter[fn[x] { return x > 2; }];",
+            "[4, 6]"
+        );
+        assert_interpreter!(
+            "fn make_adder[n] return fn[x] x + n;
+            let add = make_adder[10];
+            return add[5];",
+            "15"
+        );
+        assert_interpreter!(
+            "let numbers = fn[] { yield 1; yield 2; };
+            let gen = numbers[];
+            return gen.to_sequence[];",
+            "[1, 2]"
+        );
+    }
+
+    #[test]
+    fn call_expressions() {
+        assert_interpreter!(
+            "fn make_adder[n] return fn[x] x + n; return make_adder[1][2];",
+            "3"
+        );
+        assert_interpreter!(
+            "let fns = [fn[x] x * 2, fn[x] x * 3]; return fns[1][5];",
+            "15"
+        );
+        assert_interpreter!("return (fn[x] x + 1)[1];", "2");
+        assert_interpreter!(
+            "context obj {
+                let k = 4;
+                let table = hashmap.new[];
+                table.set[\"dec\", fn[x] x - 1];
+                fn scale[] return fn[x] x * this.k;
+            }
+            return [obj.scale[][2], obj.table.get[\"dec\"][10]];",
+            "[8, 9]"
+        );
+    }
+}
+
+pub mod params_tests {
+    #[test]
+    fn default_params() {
+        assert_interpreter!(
+            "fn f[a, b = 2] return a + b; return [f[1], f[1, 5]];",
+            "[3, 6]"
+        );
+        assert_interpreter!("fn f[a, b = a * 10] return b; return f[3];", "30");
+    }
+
+    #[test]
+    fn rest_params() {
+        assert_interpreter!(
+            "fn f[a, ...rest] return [a, rest]; return [f[1], f[1, 2, 3]];",
+            "[[1, []], [1, [2, 3]]]"
+        );
+    }
+
+    #[test]
+    fn named_args() {
+        assert_interpreter!(
+            "fn f[a = 1, b = 2, c = 3] return [a, b, c]; return f[c = 30, a = 10];",
+            "[10, 2, 30]"
+        );
+        assert_interpreter!("fn f[a, b = 2] return a - b; return f[b = 1, a = 5];", "4");
+        assert_interpreter!(
+            "let gen = fn[from, to = 3] { for (let i = from; i < to; ++i) yield i; };
+            let numbers = gen[to = 4, from = 1];
+            return numbers.to_sequence[];",
+            "[1, 2, 3]"
+        );
+    }
+}
+
+pub mod destructuring_tests {
+    #[test]
+    fn sequence_patterns() {
+        assert_interpreter!(
+            "let [a, [b, c], ...rest] = [1, [2, 3], 4, 5]; return [a, b, c, rest];",
+            "[1, 2, 3, [4, 5]]"
+        );
+        assert_interpreter!("let [a, ...rest] = [1]; return rest;", "[]");
+        assert_interpreter!(
+            "let a = 1; let b = 2; mut [a, b] = [b, a]; return [a, b];",
+            "[2, 1]"
+        );
+    }
+
+    #[test]
+    fn hashmap_patterns() {
+        assert_interpreter!(
+            "let point = hashmap.new[];
+            point.set[\"x\", 1];
+            point.set[\"y\", 2];
+            let {x, y} = point;
+            return x + y;",
+            "3"
+        );
+    }
+}
+
+pub mod match_tests {
+    #[test]
+    fn literal_and_range_patterns() {
+        assert_interpreter!(
+            "fn f[v] return match v { 0 => \"zero\", -1 => \"minus\", 1..9 => \"digit\", \"a\" => \"str\", _ => \"other\" };
+            return [f[0], f[-1], f[4], f[\"a\"], f[10]];",
+            "[zero, minus, digit, str, other]"
+        );
+    }
+
+    #[test]
+    fn sequence_patterns() {
+        assert_interpreter!(
+            "fn f[v] return match v { [] => 0, [x] => x, [a, b, ...rest] if a > b => rest, [a, ...rest] => a };
+            return [f[[]], f[[7]], f[[3, 1, 9]], f[[1, 2]]];",
+            "[0, 7, [9], 1]"
+        );
+    }
+
+    #[test]
+    fn hashmap_patterns() {
+        assert_interpreter!(
+            "let c = hashmap.new[];
+            c.set[\"kind\", \"circle\"];
+            c.set[\"r\", 2];
+            return match c { {kind: \"square\"} => 0, {kind: \"circle\", r} => r * 10 };",
+            "20"
+        );
+        assert_interpreter!(
+            "let e = error[\"NotFound\", \"no file\"];
+            return match e { {kind: \"ZeroDivision\"} => 1, {kind: \"NotFound\", message} => message };",
+            "no file"
+        );
+    }
+
+    #[test]
+    fn bindings_are_scoped() {
+        assert_interpreter!(
+            "let x = 1; let y = match 5 { x if x > 3 => x * 2, _ => 0 }; return [x, y];",
+            "[1, 10]"
+        );
+    }
+}
+
+pub mod algebra_tests {
+    #[test]
+    fn formula_arithmetic() {
+        assert_interpreter!("let f := x^2; return f * 3 + 1;", "(((x^2)*3)+1)");
+        assert_interpreter!("let f := x; return -f;", "(-x)");
+        assert_interpreter!("let f := x + 1; return f.to_string[];", "(x+1)");
+    }
+
+    #[test]
+    fn simplify() {
+        assert_interpreter!(
+            "let f := x^2 + 2*x; return simplify[f * 3 - f];",
+            "((2*(x^2))+(4*x))"
+        );
+        assert_interpreter!(
+            "let f := x*y + y*x - 3; return simplify[f];",
+            "(-3+(2*(x*y)))"
+        );
+        assert_interpreter!("let f := x*x/x - x; return simplify[f];", "0");
+    }
+
+    #[test]
+    fn exact_coefficients() {
+        assert_interpreter!(
+            "let f := (10^20 + 1)*x; return simplify[f];",
+            "(100000000000000000001*x)"
+        );
+        assert_interpreter!("let f := x/3 + x/6; return simplify[f];", "(x/2)");
+        assert_interpreter!("let f := x^3/(2*x); return simplify[f];", "((x^2)/2)");
+    }
+
+    #[test]
+    fn expand() {
+        assert_interpreter!("let f := (x + 1)^2; return expand[f];", "((1+(2*x))+(x^2))");
+        assert_interpreter!("let f := (x + 1)*(x - 1); return expand[f];", "(-1+(x^2))");
+    }
+
+    #[test]
+    fn expand_large_powers() {
+        assert_interpreter!(
+            "let f := (x + 1)^15; return expand[f];",
+            "(((((((((((((((1+(15*x))+(105*(x^2)))+(455*(x^3)))+(1365*(x^4)))+(3003*(x^5)))+(5005*(x^6)))+(6435*(x^7)))+(6435*(x^8)))+(5005*(x^9)))+(3003*(x^10)))+(1365*(x^11)))+(455*(x^12)))+(105*(x^13)))+(15*(x^14)))+(x^15))"
+        );
+        assert_interpreter!(
+            "let f := (x + 1)^20; return simplify[substitute[expand[f], \"x\", 1]];",
+            "1048576"
+        );
+    }
+
+    #[test]
+    fn substitute_and_eval() {
+        assert_interpreter!(
+            "let f := x^2 + y; return simplify[substitute[f, \"x\", 3]];",
+            "(9+y)"
+        );
+        assert_interpreter!("let f := x^2 + 1; let x = 3; return eval[f];", "10");
+    }
+
+    #[test]
+    fn diff() {
+        assert_interpreter!(
+            "let f := x^3 + 2*x + 1; return diff[f, \"x\"];",
+            "(2+(3*(x^2)))"
+        );
+        assert_interpreter!("let f := x*y^2; return diff[f, \"y\"];", "(2*(x*y))");
+        assert_interpreter!("let f := 1/x; return diff[f, \"x\"];", "(-1/(x^2))");
+    }
+
+    #[test]
+    fn diff_functions() {
+        assert_interpreter!(
+            "let f := sin[x] * x; return diff[f, \"x\"];",
+            "(((cos[x])*x)+(sin[x]))"
+        );
+        assert_interpreter!(
+            "let f := exp[2*x]; return diff[f, \"x\"];",
+            "(2*(exp[(2*x)]))"
+        );
+        assert_interpreter!("let f := ln[x^2]; return diff[f, \"x\"];", "(2/x)");
+    }
+}
+
+pub mod formula_tests {
+    #[test]
+    fn reevaluated_on_read() {
+        assert_interpreter!(
+            "let a = 2; let f := a * 10; let before = f; mut a = 5; return [before, f];",
+            "[20, 50]"
+        );
+    }
+
+    #[test]
+    fn methods_keep_formulas() {
+        assert_interpreter!(
+            "let n = 1; let xs := [n, n]; xs.len[]; mut n = 5; return xs;",
+            "[5, 5]"
+        );
+        assert_interpreter!(
+            "let n = 1; let xs := [n, n]; let ys = xs.reverse[]; mut n = 5; return [xs, ys];",
+            "[[5, 5], [1, 1]]"
+        );
+    }
+
+    #[test]
+    fn formulas_of_formulas() {
+        assert_interpreter!(
+            "let w = 2; let area := w * h; let double := area * 2; let h = 3; return double;",
+            "12"
+        );
+    }
+
+    #[test]
+    fn unknown_variables_stay_symbolic() {
+        assert_interpreter!("let a = 2; let f := a * x; return f;", "(2*x)");
+        assert_interpreter!("let f := sin[x] + 1; return f;", "((sin[x])+1)");
+    }
+
+    #[test]
+    fn redeclared_as_plain_variable() {
+        assert_interpreter!(
+            "let a = 1; let f := a + 1; let g = f; mut a = 10; return g;",
+            "2"
+        );
+        assert_interpreter!(
+            "let a = 1; let f := a + 1; let f = f; mut a = 10; return f;",
+            "2"
+        );
+    }
+}
+
+pub mod number_tests {
+    #[test]
+    fn big_integers() {
+        assert_interpreter!(
+            "fn f[n] return n == 0 ? 1 : n * f[n - 1]; return f[25];",
+            "15511210043330985984000000"
+        );
+        assert_interpreter!("return 2^100;", "1267650600228229401496703205376");
+        assert_interpreter!("return 1 << 70;", "1180591620717411303424");
+    }
+
+    #[test]
+    fn bitwise_truncates() {
+        assert_interpreter!("return [2.5 << 1, 9.9 >> 1, 7/2 << 1];", "[4, 4, 6]");
+        assert_interpreter!("return [6.7 & 3, 4.2 | 1, 5.5 bxor 1];", "[2, 5, 4]");
+    }
+
+    #[test]
+    fn huge_shifts() {
+        assert_interpreter!(
+            "return 1 << 100000000000;",
+            fails "Shift amount `100000000000` is larger than the limit of 16777216"
+        );
+        assert_interpreter!("return 1 >> 100000000000;", "0");
+    }
+
+    #[test]
+    fn exact_rationals() {
+        assert_interpreter!("return 1/10 + 2/10;", "3/10");
+        assert_interpreter!("return [7 / 2, 1/3 * 3, 2^-2];", "[7/2, 1, 1/4]");
+        assert_interpreter!("return [1/2 == 0.5, 1/2 < 2/3];", "[true, true]");
+    }
+
+    #[test]
+    fn floats_stay_floats() {
+        assert_interpreter!("return 0.1 + 0.2;", "0.30000000000000004");
+        assert_interpreter!("return 1/2 + 0.25;", "0.75");
+        assert_interpreter!("return rational[0.1] + rational[0.2];", "3/10");
+    }
+
+    #[test]
+    fn conversions() {
+        assert_interpreter!(
+            "return [int[7/2], int[-2.9], int[\"12\"], float[1/4]];",
+            "[3, -2, 12, 0.25]"
+        );
+        assert_interpreter!(
+            "return [rational[0.1], rational[\"-1.25\"], rational[6, 4]];",
+            "[1/10, -5/4, 3/2]"
+        );
+    }
+}
+
+pub mod math_tests {
+    #[test]
+    fn complex_numbers() {
+        assert_interpreter!("return (1 + 2i) * (3 - 1i);", "5+5i");
+        assert_interpreter!("return [2i^2, math.i * math.i];", "[-4+0i, -1+0i]");
+        assert_interpreter!("return complex[1, 2] / 2i;", "1-0.5i");
+        assert_interpreter!("return [1 + 2i == complex[1, 2], -1i];", "[true, -1i]");
+    }
+
+    #[test]
+    fn real_functions() {
+        assert_interpreter!(
+            "return [math.sqrt[16], math.sqrt[-4], math.abs[-3/4], math.abs[3 + 4i]];",
+            "[4, 2i, 3/4, 5]"
+        );
+        assert_interpreter!(
+            "return [math.floor[7/2], math.round[2.5], math.ceil[-1.5], math.sign[-2.5]];",
+            "[3, 3, -1, -1]"
+        );
+        assert_interpreter!(
+            "return [math.min[3, 1/2, 2.5], math.max[1, 7], math.log[8, 2], math.cos[math.pi]];",
+            "[1/2, 7, 3, -1]"
+        );
+        assert_interpreter!("return math.random_int[3, 3];", "3");
+    }
+
+    #[test]
+    fn symbolic_functions() {
+        assert_interpreter!(
+            "let f := math.sin[x^2]; return diff[f, \"x\"];",
+            "(2*(((math.cos)[(x^2)])*x))"
+        );
+        assert_interpreter!(
+            "let f := math.sin[x]; let d = diff[f, \"x\"]; let x = 0; return eval[d];",
+            "1"
+        );
+    }
+}
@@ -1,7 +1,9 @@
+use num_bigint::BigInt;
 use std::str::Chars;
 use symboscript_types::lexer::{Token, TokenKind, TokenValue};
 use symboscript_utils::report_error;
 
+#[derive(Clone)]
 pub struct Lexer<'a> {
     /// Path of the source file
     path: &'a str,
@@ -14,6 +16,9 @@ pub struct Lexer<'a> {
 
     /// Lex comments
     comment: bool,
+
+    /// Report unexpected characters and exit, otherwise they are given as `Unexpected` tokens
+    report: bool,
 }
 
 impl<'a> Lexer<'a> {
@@ -23,9 +28,16 @@ impl<'a> Lexer<'a> {
             source,
             chars: source.chars(),
             comment,
+            report: true,
         }
     }
 
+    /// Gives unexpected characters back as tokens, for callers that collect errors themselves
+    pub fn without_reports(mut self) -> Self {
+        self.report = false;
+        self
+    }
+
     pub fn tokenize(&mut self) -> Vec<Token> {
         let mut tokens = Vec::new();
 
@@ -56,7 +68,11 @@ impl<'a> Lexer<'a> {
 
         match kind {
             TokenKind::Number => {
-                value = TokenValue::Number(s.parse::<f64>().unwrap_or_default());
+                value = match (s.parse::<BigInt>(), s.strip_suffix('i')) {
+                    (_, Some(im)) => TokenValue::Imaginary(im.parse::<f64>().unwrap_or_default()),
+                    (Ok(i), _) => TokenValue::Integer(i),
+                    (Err(_), _) => TokenValue::Number(s.parse::<f64>().unwrap_or_default()),
+                };
             }
 
             TokenKind::Identifier => {
@@ -73,7 +89,7 @@ impl<'a> Lexer<'a> {
 
             TokenKind::DocComment => value = TokenValue::Str(s),
 
-            TokenKind::Unexpected => {
+            TokenKind::Unexpected if self.report => {
                 report_error(self.path, self.source, "Unexpected token", start, end)
             }
             _ => {}
@@ -88,7 +104,7 @@ impl<'a> Lexer<'a> {
     }
 
     fn next_kind(&mut self) -> TokenKind {
-        while let Some(c) = self.next() {
+        if let Some(c) = self.next() {
             match c {
                 '#' => return self.read_comment(),
 
@@ -127,7 +143,13 @@ impl<'a> Lexer<'a> {
                 '~' => return TokenKind::Tilde,
                 '?' => return TokenKind::Question,
 
-                '=' => return self.read_one_more('=', TokenKind::Equal, TokenKind::Assign),
+                '=' => {
+                    return self.read_one_more_variants(
+                        TokenKind::Assign,
+                        &['=', '>'],
+                        &[TokenKind::Equal, TokenKind::Arrow],
+                    )
+                }
                 '!' => {
                     return self.read_one_more('=', TokenKind::NotEqual, TokenKind::ExclamationMark)
                 }
@@ -192,6 +214,10 @@ impl<'a> Lexer<'a> {
             "delete" => TokenKind::Delete,
 
             "throw" => TokenKind::Throw,
+            "try" => TokenKind::Try,
+            "catch" => TokenKind::Catch,
+            "finally" => TokenKind::Finally,
+            "match" => TokenKind::Match,
 
             "import" => TokenKind::Import,
             "as" => TokenKind::As,
@@ -237,11 +263,16 @@ impl<'a> Lexer<'a> {
     fn read_dot(&mut self) -> TokenKind {
         if self.peek() == Some('.') {
             self.next();
+
+            if self.eat('.') {
+                return TokenKind::Ellipsis;
+            }
+
             return TokenKind::Range;
         } else if ("0"..="9").contains(&self.peek().unwrap_or_default().to_string().as_str()) {
             return self.read_number();
         }
-        return TokenKind::Dot;
+        TokenKind::Dot
     }
 
     fn read_number(&mut self) -> TokenKind {
@@ -251,16 +282,9 @@ impl<'a> Lexer<'a> {
                     self.next();
                 }
                 '.' | 'e' | 'E' => {
-                    if let Some(c) = self.peek_two() {
-                        match c {
-                            '0'..='9' => {
-                                self.next();
-                                self.next();
-                            }
-                            _ => {
-                                break;
-                            }
-                        }
+                    if let Some('0'..='9') = self.peek_two() {
+                        self.next();
+                        self.next();
                     } else {
                         break;
                     }
@@ -269,6 +293,16 @@ impl<'a> Lexer<'a> {
             };
         }
 
+        // Imaginary unit suffix, like `2i`
+        if self.peek() == Some('i')
+            && !matches!(
+                self.peek_two(),
+                Some('a'..='z' | 'A'..='Z' | '_' | '0'..='9')
+            )
+        {
+            self.next();
+        }
+
         TokenKind::Number
     }
 
@@ -276,13 +310,11 @@ impl<'a> Lexer<'a> {
         if self.eat('/') {
             while let Some(c) = self.peek() {
                 self.next();
-                if c == '/' {
-                    if self.eat('#') {
-                        if self.comment {
-                            return TokenKind::DocComment;
-                        } else {
-                            return TokenKind::Skip;
-                        }
+                if c == '/' && self.eat('#') {
+                    if self.comment {
+                        return TokenKind::DocComment;
+                    } else {
+                        return TokenKind::Skip;
                     }
                 }
             }
@@ -348,9 +380,9 @@ impl<'a> Lexer<'a> {
         match self.peek() {
             Some(c) if c == ch => {
                 self.next();
-                return kind_expected;
+                kind_expected
             }
-            _ => return kind_unexpected,
+            _ => kind_unexpected,
         }
     }
 
@@ -363,10 +395,10 @@ impl<'a> Lexer<'a> {
         match self.peek() {
             Some(c) if char_expected.contains(&c) => {
                 self.next();
-                return kind_expected[char_expected.iter().position(|&x| x == c).unwrap()];
+                kind_expected[char_expected.iter().position(|&x| x == c).unwrap()]
             }
 
-            _ => return kind_unexpected,
+            _ => kind_unexpected,
         }
     }
 
@@ -2,8 +2,9 @@ use clap::Parser;
 use serde_json::json;
 use std::fs::OpenOptions;
 use symboscript_parser as parser;
+use symboscript_utils::{diagnostic::MessageFormat, report_parse_errors};
 
-mod optimizer;
+use symboscript_optimizer as optimizer;
 
 #[derive(Parser, Debug)]
 #[clap(author, version, about, long_about = None)]
@@ -14,6 +15,10 @@ struct Args {
     /// Enable debug mode (prints the AST)
     #[clap(short, long)]
     debug: bool,
+
+    /// Format of error messages: human, json or sarif
+    #[clap(long, default_value = "human")]
+    message_format: MessageFormat,
 }
 
 fn main() {
@@ -24,7 +29,10 @@ fn main() {
 
     let mut parser = parser::Parser::new(&args.path, text);
 
-    let ast = parser.parse();
+    let ast = match parser.parse() {
+        Ok(ast) => ast,
+        Err(errors) => report_parse_errors(&args.path, text, &errors, args.message_format),
+    };
 
     let optimized_ast = optimizer::optimize(&ast);
 
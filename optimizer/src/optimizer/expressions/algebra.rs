@@ -0,0 +1,621 @@
+use std::ops;
+
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::{FromPrimitive, One, Signed, ToPrimitive, Zero};
+use symboscript_types::{lexer::TokenValue, parser::*};
+
+use super::optim_binary_expression;
+
+/// Folds constants, removes neutral elements and collects like terms and factors
+pub fn simplify(expression: &Expression) -> Expression {
+    match expression {
+        Expression::BinaryExpression(binary_expression) => {
+            let left = simplify(&binary_expression.left);
+            let right = simplify(&binary_expression.right);
+
+            match binary_expression.operator {
+                BinaryOperator::Add | BinaryOperator::Substract => {
+                    collect_terms(&binary(left, binary_expression.operator, right))
+                }
+                BinaryOperator::Multiply | BinaryOperator::Divide => {
+                    collect_factors(&binary(left, binary_expression.operator, right))
+                }
+                BinaryOperator::Power => simplify_power(left, right),
+                operator => optim_binary_expression(&BinaryExpression {
+                    node: binary_expression.node,
+                    left,
+                    operator,
+                    right,
+                }),
+            }
+        }
+        Expression::UnaryExpression(unary_expression) => match unary_expression.operator {
+            UnaryOperator::Plus => simplify(&unary_expression.right),
+            UnaryOperator::Minus => collect_factors(&binary(
+                Number::integer(-1).to_expression(),
+                BinaryOperator::Multiply,
+                simplify(&unary_expression.right),
+            )),
+            _ => expression.clone(),
+        },
+        _ => expression.clone(),
+    }
+}
+
+/// Multiplies out products of sums and integer powers of sums, then simplifies
+pub fn expand(expression: &Expression) -> Expression {
+    let expanded = match expression {
+        Expression::BinaryExpression(binary_expression) => {
+            let left = expand(&binary_expression.left);
+            let right = expand(&binary_expression.right);
+
+            match (
+                binary_expression.operator,
+                as_number(&right).and_then(|n| n.to_usize()),
+            ) {
+                (BinaryOperator::Multiply, _) => distribute(&left, &right),
+                // Like terms are collected after every step, so the product grows with
+                // the number of distinct terms instead of doubling
+                (BinaryOperator::Power, Some(n)) if n >= 2 => {
+                    let mut product = left.clone();
+
+                    for _ in 1..n {
+                        product = distribute(&product, &left);
+                    }
+
+                    product
+                }
+                (operator, _) => binary(left, operator, right),
+            }
+        }
+        Expression::UnaryExpression(unary_expression)
+            if unary_expression.operator == UnaryOperator::Minus =>
+        {
+            distribute(
+                &Number::integer(-1).to_expression(),
+                &expand(&unary_expression.right),
+            )
+        }
+        _ => expression.clone(),
+    };
+
+    simplify(&expanded)
+}
+
+/// Replaces every `name` identifier with the replacement
+pub fn substitute(expression: &Expression, name: &str, replacement: &Expression) -> Expression {
+    let sub = |expression: &Expression| substitute(expression, name, replacement);
+
+    match expression {
+        Expression::Identifier(id) if id.name == name => replacement.clone(),
+        Expression::BinaryExpression(binary_expression) => {
+            Expression::BinaryExpression(Box::new(BinaryExpression {
+                node: binary_expression.node,
+                left: sub(&binary_expression.left),
+                operator: binary_expression.operator,
+                right: sub(&binary_expression.right),
+            }))
+        }
+        Expression::UnaryExpression(unary_expression) => {
+            Expression::UnaryExpression(Box::new(UnaryExpression {
+                node: unary_expression.node,
+                operator: unary_expression.operator,
+                right: sub(&unary_expression.right),
+            }))
+        }
+        Expression::ConditionalExpression(conditional_expression) => {
+            Expression::ConditionalExpression(Box::new(ConditionalExpression {
+                node: conditional_expression.node,
+                test: sub(&conditional_expression.test),
+                consequent: sub(&conditional_expression.consequent),
+                alternate: sub(&conditional_expression.alternate),
+            }))
+        }
+        Expression::CallExpression(call_expression) => {
+            Expression::CallExpression(Box::new(CallExpression {
+                node: call_expression.node,
+                callee: call_expression.callee.clone(),
+                arguments: sub(&call_expression.arguments),
+                named_arguments: call_expression.named_arguments.clone(),
+            }))
+        }
+        Expression::SequenceExpression(sequence_expression) => {
+            Expression::SequenceExpression(Box::new(SequenceExpression {
+                node: sequence_expression.node,
+                expressions: sequence_expression.expressions.iter().map(sub).collect(),
+            }))
+        }
+        _ => expression.clone(),
+    }
+}
+
+// ------------------------------ Numbers ------------------------------
+
+/// Coefficient or exponent, exact unless a float literal is involved, so `x/3 + x/6` is `x/2`
+#[derive(Clone, Debug, PartialEq)]
+pub(super) enum Number {
+    Exact(BigRational),
+    Float(f64),
+}
+
+impl Number {
+    pub(super) fn integer(n: i64) -> Self {
+        Number::Exact(BigRational::from_integer(n.into()))
+    }
+
+    fn to_f64(&self) -> f64 {
+        match self {
+            Number::Exact(r) => r.to_f64().unwrap_or(f64::NAN),
+            Number::Float(f) => *f,
+        }
+    }
+
+    fn is_zero(&self) -> bool {
+        match self {
+            Number::Exact(r) => r.is_zero(),
+            Number::Float(f) => *f == 0.0,
+        }
+    }
+
+    fn is_one(&self) -> bool {
+        match self {
+            Number::Exact(r) => r.is_one(),
+            Number::Float(f) => *f == 1.0,
+        }
+    }
+
+    fn is_negative(&self) -> bool {
+        match self {
+            Number::Exact(r) => r.is_negative(),
+            Number::Float(f) => *f < 0.0,
+        }
+    }
+
+    /// Whole number that fits in a `usize`
+    fn to_usize(&self) -> Option<usize> {
+        match self {
+            Number::Exact(r) if r.is_integer() => r.to_integer().to_usize(),
+            Number::Float(f) if f.fract() == 0.0 => f.to_usize(),
+            _ => None,
+        }
+    }
+
+    /// Quotient, `None` for a division by zero
+    fn checked_div(&self, divisor: &Number) -> Option<Number> {
+        if divisor.is_zero() {
+            return None;
+        }
+
+        Some(match (self, divisor) {
+            (Number::Exact(a), Number::Exact(b)) => Number::Exact(a / b),
+            (a, b) => Number::Float(a.to_f64() / b.to_f64()),
+        })
+    }
+
+    /// Power, `None` if it isn't a rational number like `2^(1/2)` or `0^-1`
+    fn pow(&self, exponent: &Number) -> Option<Number> {
+        match (self, exponent) {
+            (Number::Exact(base), Number::Exact(exponent)) => {
+                let exponent = exponent
+                    .is_integer()
+                    .then(|| exponent.to_integer().to_i32())??;
+
+                if base.is_zero() && exponent < 0 {
+                    return None;
+                }
+
+                Some(Number::Exact(base.pow(exponent)))
+            }
+            (base, exponent) => Some(Number::Float(base.to_f64().powf(exponent.to_f64()))),
+        }
+    }
+
+    /// Fractions are written as quotients of integer literals
+    pub(super) fn to_expression(&self) -> Expression {
+        match self {
+            Number::Exact(r) if r.is_integer() => integer(r.to_integer()),
+            Number::Exact(r) => binary(
+                integer(r.numer().clone()),
+                BinaryOperator::Divide,
+                integer(r.denom().clone()),
+            ),
+            Number::Float(f) => number(*f),
+        }
+    }
+}
+
+macro_rules! number_op {
+    ($trait:ident, $method:ident, $op:tt) => {
+        impl ops::$trait for Number {
+            type Output = Number;
+
+            fn $method(self, other: Number) -> Number {
+                match (self, other) {
+                    (Number::Exact(a), Number::Exact(b)) => Number::Exact(a $op b),
+                    (a, b) => Number::Float(a.to_f64() $op b.to_f64()),
+                }
+            }
+        }
+    };
+}
+
+number_op!(Add, add, +);
+number_op!(Sub, sub, -);
+number_op!(Mul, mul, *);
+
+impl ops::Neg for Number {
+    type Output = Number;
+
+    fn neg(self) -> Number {
+        match self {
+            Number::Exact(r) => Number::Exact(-r),
+            Number::Float(f) => Number::Float(-f),
+        }
+    }
+}
+
+// ------------------------------ Sums ------------------------------
+
+/// Term of a sum, a coefficient and the monomial it multiplies, `None` for the constant
+type Term = (Number, Option<Expression>);
+
+/// Terms of a sum with their coefficients, `3*x - 2` is `[(3, x), (-2, None)]`
+fn flatten_sum(expression: &Expression, sign: &Number, terms: &mut Vec<Term>) {
+    if let Expression::BinaryExpression(binary_expression) = expression {
+        match binary_expression.operator {
+            BinaryOperator::Add => {
+                flatten_sum(&binary_expression.left, sign, terms);
+                flatten_sum(&binary_expression.right, sign, terms);
+                return;
+            }
+            BinaryOperator::Substract => {
+                flatten_sum(&binary_expression.left, sign, terms);
+                flatten_sum(&binary_expression.right, &-sign.clone(), terms);
+                return;
+            }
+            _ => {}
+        }
+    }
+
+    match split_coefficient(expression) {
+        // Numbers are distributed over sums, `3*(x + 1)` is `3*x + 3`
+        (coefficient, Some(sum)) if is_sum(&sum) => {
+            flatten_sum(&sum, &(sign.clone() * coefficient), terms)
+        }
+        (coefficient, monomial) => terms.push((sign.clone() * coefficient, monomial)),
+    }
+}
+
+fn collect_terms(expression: &Expression) -> Expression {
+    let mut terms = vec![];
+    flatten_sum(expression, &Number::integer(1), &mut terms);
+
+    sum(add_like_terms(terms))
+}
+
+/// Adds up like terms, the constant goes first like in `sort_plus_binary_expression`
+fn add_like_terms(terms: Vec<Term>) -> Vec<Term> {
+    let mut constant = Number::integer(0);
+    let mut collected: Vec<(String, Number, Expression)> = vec![];
+
+    for (coefficient, monomial) in terms {
+        match monomial {
+            None => constant = constant + coefficient,
+            Some(monomial) => {
+                let key = monomial.to_string();
+
+                match collected.iter_mut().find(|(k, _, _)| *k == key) {
+                    Some((_, c, _)) => *c = c.clone() + coefficient,
+                    None => collected.push((key, coefficient, monomial)),
+                }
+            }
+        }
+    }
+
+    std::iter::once((constant, None))
+        .chain(collected.into_iter().map(|(_, c, m)| (c, Some(m))))
+        .filter(|(coefficient, _)| !coefficient.is_zero())
+        .collect()
+}
+
+/// Sum of the terms, negative coefficients are subtracted
+fn sum(terms: Vec<Term>) -> Expression {
+    let mut sum: Option<Expression> = None;
+
+    for (coefficient, monomial) in terms {
+        let term = |coefficient: Number| quotient(coefficient, monomial.clone(), None);
+
+        sum = Some(match sum {
+            None => term(coefficient),
+            Some(sum) if coefficient.is_negative() => {
+                binary(sum, BinaryOperator::Substract, term(-coefficient))
+            }
+            Some(sum) => binary(sum, BinaryOperator::Add, term(coefficient)),
+        });
+    }
+
+    sum.unwrap_or_else(|| Number::integer(0).to_expression())
+}
+
+/// Products of every term of the left side with every term of the right side
+fn distribute(left: &Expression, right: &Expression) -> Expression {
+    let one = Number::integer(1);
+
+    let mut left_terms = vec![];
+    flatten_sum(left, &one, &mut left_terms);
+
+    let mut right_terms = vec![];
+    flatten_sum(right, &one, &mut right_terms);
+
+    let mut terms = vec![];
+
+    for (left_coefficient, left_monomial) in &left_terms {
+        for (right_coefficient, right_monomial) in &right_terms {
+            let coefficient = left_coefficient.clone() * right_coefficient.clone();
+
+            let term = match (left_monomial, right_monomial) {
+                (Some(l), Some(r)) => binary(l.clone(), BinaryOperator::Multiply, r.clone()),
+                (Some(m), None) | (None, Some(m)) => m.clone(),
+                (None, None) => {
+                    terms.push((coefficient, None));
+                    continue;
+                }
+            };
+
+            flatten_sum(&term, &coefficient, &mut terms);
+        }
+    }
+
+    sum(add_like_terms(terms))
+}
+
+// ------------------------------ Products ------------------------------
+
+/// Factors of a product, `2*x*x` is `[2, x, x]`
+fn flatten_product(expression: &Expression, factors: &mut Vec<Expression>) {
+    match expression {
+        Expression::BinaryExpression(binary_expression)
+            if binary_expression.operator == BinaryOperator::Multiply =>
+        {
+            flatten_product(&binary_expression.left, factors);
+            flatten_product(&binary_expression.right, factors);
+        }
+        _ => factors.push(expression.clone()),
+    }
+}
+
+/// Multiplies the numbers, turns repeated factors into powers and cancels the factors
+/// of quotients, `2*x*3*x` is `6*x^2` and `x^3/(2*x)` is `x^2/2`
+fn collect_factors(expression: &Expression) -> Expression {
+    let (coefficient, powers) = collect_powers(expression);
+
+    product(coefficient, powers)
+}
+
+/// Coefficient of a product and the exponents of its factors, the factors of divisors get negative exponents
+fn collect_powers(expression: &Expression) -> (Number, Vec<(Expression, Number)>) {
+    let mut factors = vec![];
+    flatten_product(expression, &mut factors);
+
+    let mut coefficient = Number::integer(1);
+    let mut powers = vec![];
+
+    for factor in factors {
+        if let Some(n) = as_number(&factor) {
+            coefficient = coefficient * n;
+            continue;
+        }
+
+        if let Expression::BinaryExpression(binary_expression) = &factor {
+            if binary_expression.operator == BinaryOperator::Divide {
+                let (divisor, divisor_powers) = collect_powers(&binary_expression.right);
+                let (dividend, dividend_powers) = collect_powers(&binary_expression.left);
+
+                // Division by zero is left as it is
+                if let Some(quotient) = dividend.checked_div(&divisor) {
+                    coefficient = coefficient * quotient;
+
+                    for (base, exponent) in dividend_powers {
+                        add_power(&mut powers, base, exponent);
+                    }
+
+                    for (base, exponent) in divisor_powers {
+                        add_power(&mut powers, base, -exponent);
+                    }
+
+                    continue;
+                }
+            }
+        }
+
+        let (base, exponent) = split_power(&factor);
+        add_power(&mut powers, base, exponent);
+    }
+
+    (coefficient, powers)
+}
+
+fn add_power(powers: &mut Vec<(Expression, Number)>, base: Expression, exponent: Number) {
+    let key = base.to_string();
+
+    match powers.iter_mut().find(|(b, _)| b.to_string() == key) {
+        Some((_, e)) => *e = e.clone() + exponent,
+        None => powers.push((base, exponent)),
+    }
+}
+
+/// Product of the factors, the ones with negative exponents go to the divisor
+fn product(coefficient: Number, mut powers: Vec<(Expression, Number)>) -> Expression {
+    if coefficient.is_zero() {
+        return Number::integer(0).to_expression();
+    }
+
+    // Factors are sorted, so `x*y` and `y*x` are collected as the same term
+    powers.sort_by_key(|(base, _)| base.to_string());
+
+    let (numerator, denominator): (Vec<_>, Vec<_>) = powers
+        .into_iter()
+        .filter(|(_, exponent)| !exponent.is_zero())
+        .partition(|(_, exponent)| !exponent.is_negative());
+
+    let denominator = monomial(
+        denominator
+            .into_iter()
+            .map(|(base, exponent)| (base, -exponent))
+            .collect(),
+    );
+
+    quotient(coefficient, monomial(numerator), denominator)
+}
+
+fn monomial(powers: Vec<(Expression, Number)>) -> Option<Expression> {
+    powers
+        .into_iter()
+        .map(|(base, exponent)| simplify_power(base, exponent.to_expression()))
+        .reduce(|product, factor| binary(product, BinaryOperator::Multiply, factor))
+}
+
+fn simplify_power(base: Expression, exponent: Expression) -> Expression {
+    match (as_number(&base), as_number(&exponent)) {
+        (Some(b), Some(e)) => match b.pow(&e) {
+            Some(power) => power.to_expression(),
+            None => binary(base, BinaryOperator::Power, exponent),
+        },
+        (_, Some(e)) if e.is_zero() => Number::integer(1).to_expression(),
+        (_, Some(e)) if e.is_one() => base,
+        (Some(b), _) if b.is_one() => Number::integer(1).to_expression(),
+        (None, Some(outer)) => match split_power(&base) {
+            // (x^2)^3 is x^6
+            (inner_base, inner) if !inner.is_one() => binary(
+                inner_base,
+                BinaryOperator::Power,
+                (inner * outer).to_expression(),
+            ),
+            _ => binary(base, BinaryOperator::Power, outer.to_expression()),
+        },
+        _ => binary(base, BinaryOperator::Power, exponent),
+    }
+}
+
+// ------------------------------ Helpers ------------------------------
+
+/// Coefficient and the rest of a product, `3*x*x` is `(3, x^2)`,
+/// the factors are put in order so like terms get the same monomial
+fn split_coefficient(expression: &Expression) -> Term {
+    let (coefficient, powers) = collect_powers(expression);
+
+    if coefficient.is_zero() || powers.iter().all(|(_, exponent)| exponent.is_zero()) {
+        return (coefficient, None);
+    }
+
+    (coefficient, Some(product(Number::integer(1), powers)))
+}
+
+fn split_power(expression: &Expression) -> (Expression, Number) {
+    if let Expression::BinaryExpression(binary_expression) = expression {
+        if binary_expression.operator == BinaryOperator::Power {
+            if let Some(n) = as_number(&binary_expression.right) {
+                return (binary_expression.left.clone(), n);
+            }
+        }
+    }
+
+    (expression.clone(), Number::integer(1))
+}
+
+fn is_sum(expression: &Expression) -> bool {
+    matches!(
+        expression,
+        Expression::BinaryExpression(binary_expression)
+            if matches!(binary_expression.operator, BinaryOperator::Add | BinaryOperator::Substract)
+    )
+}
+
+/// `coefficient*numerator/denominator`, the denominator of a fraction goes to the divisor
+fn quotient(
+    coefficient: Number,
+    numerator: Option<Expression>,
+    denominator: Option<Expression>,
+) -> Expression {
+    let (coefficient, divisor) = match coefficient {
+        Number::Exact(r) if !r.is_integer() => (
+            Number::Exact(BigRational::from_integer(r.numer().clone())),
+            Some(integer(r.denom().clone())),
+        ),
+        coefficient => (coefficient, None),
+    };
+
+    let numerator = match numerator {
+        Some(monomial) if coefficient.is_one() => monomial,
+        Some(monomial) => binary(
+            coefficient.to_expression(),
+            BinaryOperator::Multiply,
+            monomial,
+        ),
+        None => coefficient.to_expression(),
+    };
+
+    let denominator = match (divisor, denominator) {
+        (Some(divisor), Some(denominator)) => {
+            Some(binary(divisor, BinaryOperator::Multiply, denominator))
+        }
+        (divisor, denominator) => divisor.or(denominator),
+    };
+
+    match denominator {
+        Some(denominator) => binary(numerator, BinaryOperator::Divide, denominator),
+        None => numerator,
+    }
+}
+
+/// Integer literals are exact, other number literals are floats
+pub(super) fn as_number(expression: &Expression) -> Option<Number> {
+    match expression {
+        Expression::Literal(literal) => match &literal.value {
+            TokenValue::Integer(i) => Some(Number::Exact(BigRational::from_integer(i.clone()))),
+            TokenValue::Number(n) => Some(Number::Float(*n)),
+            _ => None,
+        },
+        Expression::UnaryExpression(unary_expression)
+            if unary_expression.operator == UnaryOperator::Minus =>
+        {
+            as_number(&unary_expression.right).map(|n| -n)
+        }
+        // Fractions, `1/3`
+        Expression::BinaryExpression(binary_expression)
+            if binary_expression.operator == BinaryOperator::Divide =>
+        {
+            as_number(&binary_expression.left)?.checked_div(&as_number(&binary_expression.right)?)
+        }
+        _ => None,
+    }
+}
+
+/// Whole coefficients are written as integer literals
+pub(super) fn number(n: f64) -> Expression {
+    match BigInt::from_f64(n) {
+        Some(i) if n.fract() == 0.0 => integer(i),
+        _ => literal(TokenValue::Number(n)),
+    }
+}
+
+fn integer(i: BigInt) -> Expression {
+    literal(TokenValue::Integer(i))
+}
+
+fn literal(value: TokenValue) -> Expression {
+    Expression::Literal(Literal {
+        node: Node::new(0, 0),
+        value,
+    })
+}
+
+pub(super) fn binary(left: Expression, operator: BinaryOperator, right: Expression) -> Expression {
+    Expression::BinaryExpression(Box::new(BinaryExpression {
+        node: Node::new(0, 0),
+        left,
+        operator,
+        right,
+    }))
+}
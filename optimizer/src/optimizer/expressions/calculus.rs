@@ -0,0 +1,223 @@
+use symboscript_types::parser::*;
+
+use super::algebra::{as_number, binary, number, simplify, Number};
+
+/// Simplified derivative of the expression with respect to `var`,
+/// the part that can't be differentiated is given back as the error
+pub fn diff(expression: &Expression, var: &str) -> Result<Expression, Expression> {
+    Ok(simplify(&derivative(expression, var)?))
+}
+
+fn derivative(expression: &Expression, var: &str) -> Result<Expression, Expression> {
+    if !depends_on(expression, var) {
+        return Ok(number(0.0));
+    }
+
+    match expression {
+        Expression::Identifier(_) => Ok(number(1.0)),
+        Expression::BinaryExpression(binary_expression) => {
+            let u = &binary_expression.left;
+            let v = &binary_expression.right;
+
+            match binary_expression.operator {
+                BinaryOperator::Add | BinaryOperator::Substract => Ok(binary(
+                    derivative(u, var)?,
+                    binary_expression.operator,
+                    derivative(v, var)?,
+                )),
+                // (u*v)' = u'*v + u*v'
+                BinaryOperator::Multiply => Ok(binary(
+                    mul(derivative(u, var)?, v.clone()),
+                    BinaryOperator::Add,
+                    mul(u.clone(), derivative(v, var)?),
+                )),
+                // (u/v)' = (u'*v - u*v')/v^2
+                BinaryOperator::Divide => Ok(binary(
+                    binary(
+                        mul(derivative(u, var)?, v.clone()),
+                        BinaryOperator::Substract,
+                        mul(u.clone(), derivative(v, var)?),
+                    ),
+                    BinaryOperator::Divide,
+                    pow(v.clone(), number(2.0)),
+                )),
+                BinaryOperator::Power => power_derivative(u, v, var, binary_expression.node),
+                _ => Err(expression.clone()),
+            }
+        }
+        Expression::UnaryExpression(unary_expression) => match unary_expression.operator {
+            UnaryOperator::Plus => derivative(&unary_expression.right, var),
+            UnaryOperator::Minus => {
+                Ok(mul(number(-1.0), derivative(&unary_expression.right, var)?))
+            }
+            _ => Err(expression.clone()),
+        },
+        Expression::CallExpression(call_expression) => {
+            let arguments = match &call_expression.arguments {
+                Expression::SequenceExpression(sequence) => &sequence.expressions,
+                _ => return Err(expression.clone()),
+            };
+
+            match (function_name(&call_expression.callee), arguments.as_slice()) {
+                (Some(name), [u]) if call_expression.named_arguments.is_empty() => {
+                    // Chain rule, f[u]' = f'[u]*u'
+                    let outer =
+                        function_derivative(&call_expression.callee, name, u, call_expression.node)
+                            .ok_or_else(|| expression.clone())?;
+
+                    Ok(mul(outer, derivative(u, var)?))
+                }
+                _ => Err(expression.clone()),
+            }
+        }
+        _ => Err(expression.clone()),
+    }
+}
+
+/// `node` is the span of the power, given to the `ln` calls it brings in
+fn power_derivative(
+    u: &Expression,
+    v: &Expression,
+    var: &str,
+    node: Node,
+) -> Result<Expression, Expression> {
+    let ln = |u: &Expression| call(&name_callee("ln", node), u.clone(), node);
+
+    // (u^n)' = n*u^(n-1)*u'
+    if !depends_on(v, var) {
+        let exponent = match as_number(v) {
+            Some(n) => (n - Number::integer(1)).to_expression(),
+            None => binary(v.clone(), BinaryOperator::Substract, number(1.0)),
+        };
+
+        return Ok(mul(
+            mul(v.clone(), pow(u.clone(), exponent)),
+            derivative(u, var)?,
+        ));
+    }
+
+    // (a^v)' = a^v*ln[a]*v'
+    if !depends_on(u, var) {
+        return Ok(mul(
+            mul(pow(u.clone(), v.clone()), ln(u)),
+            derivative(v, var)?,
+        ));
+    }
+
+    // (u^v)' = u^v*(v'*ln[u] + v*u'/u)
+    Ok(mul(
+        pow(u.clone(), v.clone()),
+        binary(
+            mul(derivative(v, var)?, ln(u)),
+            BinaryOperator::Add,
+            binary(
+                mul(v.clone(), derivative(u, var)?),
+                BinaryOperator::Divide,
+                u.clone(),
+            ),
+        ),
+    ))
+}
+
+/// Derivative of a known function at `u`, the callee keeps its form so `math.sin` gives `math.cos`,
+/// the new calls get the span of the differentiated call
+fn function_derivative(
+    callee: &Expression,
+    name: &str,
+    u: &Expression,
+    node: Node,
+) -> Option<Expression> {
+    let f = |name: &str| call(&rename(callee, name), u.clone(), node);
+
+    Some(match name {
+        "sin" => f("cos"),
+        "cos" => mul(number(-1.0), f("sin")),
+        "tan" => binary(
+            number(1.0),
+            BinaryOperator::Divide,
+            pow(f("cos"), number(2.0)),
+        ),
+        "exp" => f("exp"),
+        "ln" => binary(number(1.0), BinaryOperator::Divide, u.clone()),
+        "sqrt" => binary(
+            number(1.0),
+            BinaryOperator::Divide,
+            mul(number(2.0), f("sqrt")),
+        ),
+        _ => return None,
+    })
+}
+
+/// Whether the expression has the variable in it
+fn depends_on(expression: &Expression, var: &str) -> bool {
+    match expression {
+        Expression::Identifier(id) => id.name == var,
+        Expression::BinaryExpression(binary_expression) => {
+            depends_on(&binary_expression.left, var) || depends_on(&binary_expression.right, var)
+        }
+        Expression::UnaryExpression(unary_expression) => depends_on(&unary_expression.right, var),
+        Expression::CallExpression(call_expression) => depends_on(&call_expression.arguments, var),
+        Expression::SequenceExpression(sequence_expression) => sequence_expression
+            .expressions
+            .iter()
+            .any(|expression| depends_on(expression, var)),
+        Expression::ConditionalExpression(conditional_expression) => {
+            depends_on(&conditional_expression.test, var)
+                || depends_on(&conditional_expression.consequent, var)
+                || depends_on(&conditional_expression.alternate, var)
+        }
+        _ => false,
+    }
+}
+
+fn function_name(callee: &Expression) -> Option<&str> {
+    match callee {
+        Expression::Identifier(id) => Some(&id.name),
+        Expression::MemberExpression(member_expression) if !member_expression.is_expr => {
+            function_name(&member_expression.property)
+        }
+        _ => None,
+    }
+}
+
+fn rename(callee: &Expression, name: &str) -> Expression {
+    match callee {
+        Expression::MemberExpression(member_expression) => {
+            Expression::MemberExpression(Box::new(MemberExpression {
+                node: member_expression.node,
+                object: member_expression.object.clone(),
+                property: rename(&member_expression.property, name),
+                is_expr: false,
+            }))
+        }
+        Expression::Identifier(id) => name_callee(name, id.node),
+        _ => unreachable!("Only callees with a function name are renamed"),
+    }
+}
+
+fn name_callee(name: &str, node: Node) -> Expression {
+    Expression::Identifier(Identifier {
+        node,
+        name: name.to_owned(),
+    })
+}
+
+fn call(callee: &Expression, argument: Expression, node: Node) -> Expression {
+    Expression::CallExpression(Box::new(CallExpression {
+        node,
+        callee: callee.clone(),
+        arguments: Expression::SequenceExpression(Box::new(SequenceExpression {
+            node,
+            expressions: vec![argument],
+        })),
+        named_arguments: vec![],
+    }))
+}
+
+fn mul(left: Expression, right: Expression) -> Expression {
+    binary(left, BinaryOperator::Multiply, right)
+}
+
+fn pow(base: Expression, exponent: Expression) -> Expression {
+    binary(base, BinaryOperator::Power, exponent)
+}
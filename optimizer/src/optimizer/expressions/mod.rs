@@ -1,4 +1,7 @@
-use symboscript_types::parser::*;
+use symboscript_types::{lexer::TokenValue, parser::*};
+
+pub mod algebra;
+pub mod calculus;
 
 pub fn optim_expression(expression_stmt: &Expression) -> Expression {
     match expression_stmt {
@@ -22,44 +25,46 @@ pub fn optim_binary_expression(binary_expression: &BinaryExpression) -> Expressi
     let left = optim_expression_sub(&binary_expression.left);
     let right = optim_expression_sub(&binary_expression.right);
 
-    match (left.clone(), right.clone()) {
-        (Expression::Literal(left), Expression::Literal(right)) => {
-            match binary_expression.operator {
-                BinaryOperator::Add => {
-                    return Expression::Literal(Literal {
-                        node: binary_expression.node.clone(),
-                        value: left.value + right.value,
-                    })
-                }
-                BinaryOperator::Substract => {
-                    return Expression::Literal(Literal {
-                        node: binary_expression.node.clone(),
-                        value: left.value - right.value,
-                    })
-                }
-                BinaryOperator::Multiply => {
-                    return Expression::Literal(Literal {
-                        node: binary_expression.node.clone(),
-                        value: left.value * right.value,
-                    })
-                }
-                BinaryOperator::Divide => {
-                    return Expression::Literal(Literal {
-                        node: binary_expression.node.clone(),
-                        value: left.value / right.value,
-                    })
-                }
-                _ => {}
+    if let (Expression::Literal(left), Expression::Literal(right)) = (left.clone(), right.clone()) {
+        // Complex numbers are left to the interpreter
+        let imaginary = matches!(left.value, TokenValue::Imaginary(_))
+            || matches!(right.value, TokenValue::Imaginary(_));
+
+        match binary_expression.operator {
+            _ if imaginary => {}
+            BinaryOperator::Add => {
+                return Expression::Literal(Literal {
+                    node: binary_expression.node,
+                    value: left.value + right.value,
+                })
+            }
+            BinaryOperator::Substract => {
+                return Expression::Literal(Literal {
+                    node: binary_expression.node,
+                    value: left.value - right.value,
+                })
+            }
+            BinaryOperator::Multiply => {
+                return Expression::Literal(Literal {
+                    node: binary_expression.node,
+                    value: left.value * right.value,
+                })
+            }
+            BinaryOperator::Divide => {
+                return Expression::Literal(Literal {
+                    node: binary_expression.node,
+                    value: left.value / right.value,
+                })
             }
+            _ => {}
         }
-        _ => {}
     }
 
     Expression::BinaryExpression(Box::new(BinaryExpression {
         left,
         right,
-        operator: binary_expression.operator.clone(),
-        node: binary_expression.node.clone(),
+        operator: binary_expression.operator,
+        node: binary_expression.node,
     }))
 }
 
@@ -84,8 +89,8 @@ fn sort_plus_binary_expression(binary_expression: &BinaryExpression) -> BinaryEx
     unflat_plus_binary_expression(&flat)
 }
 
-fn unflat_plus_binary_expression(flat: &Vec<Expression>) -> BinaryExpression {
-    let mut flat = flat.clone();
+fn unflat_plus_binary_expression(flat: &[Expression]) -> BinaryExpression {
+    let mut flat = flat.to_owned();
 
     flat.reverse();
 
@@ -1,6 +1,6 @@
 use symboscript_types::parser::*;
 
-mod expressions;
+pub mod expressions;
 
 pub fn optimize(ast: &Ast) -> Ast {
     Ast {
@@ -22,7 +22,7 @@ fn optim_body(body: &BlockStatement) -> BlockStatement {
         match statement {
             Statement::ExpressionStatement(expression) => {
                 new_body.push(Statement::ExpressionStatement(
-                    expressions::optim_expression(&expression),
+                    expressions::optim_expression(expression),
                 ));
             }
 
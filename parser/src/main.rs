@@ -1,6 +1,7 @@
 use clap::Parser;
 use serde_json::json;
 use std::fs::OpenOptions;
+use symboscript_utils::{diagnostic::MessageFormat, report_parse_errors};
 
 mod parser;
 
@@ -13,6 +14,10 @@ struct Args {
     /// Enable debug mode (prints the AST)
     #[clap(short, long)]
     debug: bool,
+
+    /// Format of error messages: human, json or sarif
+    #[clap(long, default_value = "human")]
+    message_format: MessageFormat,
 }
 
 fn main() {
@@ -23,7 +28,10 @@ fn main() {
 
     let mut parser = parser::Parser::new(&args.path, text);
 
-    let ast = parser.parse();
+    let ast = match parser.parse() {
+        Ok(ast) => ast,
+        Err(errors) => report_parse_errors(&args.path, text, &errors, args.message_format),
+    };
 
     println!("{}", ast);
 
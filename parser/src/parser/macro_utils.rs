@@ -95,10 +95,7 @@ macro_rules! word_stmt {
                 return word_stmt_build!($self, $Kind, start, argument, $Stmt);
             }
 
-            got => {
-                $self.report_expected(start, $Kind, got);
-                unreachable!("Report ends proccess");
-            }
+            got => unreachable!("This function can't be called for other tokens: ({})", got),
         }
     }};
 }
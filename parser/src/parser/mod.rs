@@ -3,15 +3,11 @@ use symboscript_types::{
     lexer::{Token, TokenKind, TokenValue},
     parser::*,
 };
-use symboscript_utils::report_error;
 
 #[macro_use]
 mod macro_utils;
 
 pub struct Parser<'a> {
-    /// Path of the source file
-    path: &'a str,
-
     /// Source Text
     source: &'a str,
 
@@ -21,24 +17,44 @@ pub struct Parser<'a> {
     cur_token: Token,
 
     prev_token_end: usize,
+
+    /// Whether the function being parsed has a `yield`, `None` outside of functions
+    yields: Option<bool>,
+
+    /// Whether the function being parsed is async, they can't have a `yield`
+    in_async: bool,
+
+    errors: Vec<ParseError>,
+
+    /// Set by an error until the next statement, so one mistake is reported once
+    recovering: bool,
 }
 
 impl<'a> Parser<'a> {
     pub fn new(path: &'a str, source: &'a str) -> Self {
         Self {
-            path,
             source,
-            lexer: Lexer::new(path, source, false),
+            lexer: Lexer::new(path, source, false).without_reports(),
             cur_token: Token::default(),
             prev_token_end: 0,
+            yields: None,
+            in_async: false,
+            errors: vec![],
+            recovering: false,
         }
     }
 
-    pub fn parse(&mut self) -> Ast {
+    /// Parses the whole source, all syntax errors are collected instead of stopping at the first one
+    pub fn parse(&mut self) -> Result<Ast, Vec<ParseError>> {
         self.eat(TokenKind::Start);
-        return Ast {
-            program: self.program(),
-        };
+
+        let program = self.program();
+
+        if self.errors.is_empty() {
+            Ok(Ast { program })
+        } else {
+            Err(std::mem::take(&mut self.errors))
+        }
     }
 
     // -------------------- program ------------------------
@@ -49,7 +65,20 @@ impl<'a> Parser<'a> {
                 start: 0,
                 end: self.source.len(),
             },
-            body: self.body(),
+            body: {
+                let mut body = self.body();
+
+                // Closing brace without an opening one
+                while self.at(TokenKind::RAngle) {
+                    self.report_expected(self.cur_token.start, "statement", self.cur_kind());
+                    self.advance();
+                    self.recovering = false;
+
+                    body.extend(self.body());
+                }
+
+                body
+            },
         }
     }
 
@@ -60,7 +89,12 @@ impl<'a> Parser<'a> {
             match self.cur_kind() {
                 TokenKind::Eof | TokenKind::RAngle => break,
                 _ => {
+                    let start = self.cur_token.start;
                     body.push(self.statement());
+
+                    if self.recovering {
+                        self.synchronize(start);
+                    }
                 }
             }
         }
@@ -68,6 +102,26 @@ impl<'a> Parser<'a> {
         body
     }
 
+    /// Skips the rest of a statement with an error, up to the next `;` or `}`
+    fn synchronize(&mut self, start: usize) {
+        // An error at the end of the statement leaves the parser at the boundary already
+        let at_boundary = self.cur_token.start != start
+            && self.source[..self.prev_token_end].ends_with([';', '}']);
+
+        if !at_boundary {
+            while !self.at(TokenKind::Eof) && !self.at(TokenKind::RAngle) {
+                let kind = self.cur_kind();
+                self.advance();
+
+                if kind == TokenKind::Semicolon {
+                    break;
+                }
+            }
+        }
+
+        self.recovering = false;
+    }
+
     // -------------------- statements ---------------------
 
     fn statement(&mut self) -> Statement {
@@ -78,6 +132,7 @@ impl<'a> Parser<'a> {
             TokenKind::Context => self.context_decl(),
 
             TokenKind::If => self.if_stmt(),
+            TokenKind::Try => self.try_stmt(),
 
             TokenKind::For => self.for_stmt(),
             TokenKind::While => self.while_stmt(),
@@ -117,7 +172,7 @@ impl<'a> Parser<'a> {
             body.push(self.statement());
         }
 
-        return body;
+        body
     }
 
     // --------------- import statement ----------------
@@ -133,7 +188,7 @@ impl<'a> Parser<'a> {
                 TokenValue::Identifier(s) => s,
                 got => {
                     self.report_expected(self.cur_token.start, "Identifier or String", got);
-                    unreachable!("Report ends proccess");
+                    String::new()
                 }
             },
         };
@@ -230,7 +285,26 @@ impl<'a> Parser<'a> {
         self.eat(TokenKind::For);
         self.eat(TokenKind::LParen);
 
-        let init = self.var_decl(true);
+        let init_start = self.cur_token.start;
+        let is_decl = self.cur_kind() == TokenKind::Let;
+
+        if is_decl {
+            self.advance();
+        }
+
+        let left = Identifier {
+            node: Node::new(self.cur_token.start, self.cur_token.end),
+            name: format!("{}", self.cur_token.clone().value),
+        };
+        self.eat(TokenKind::Identifier);
+
+        match self.cur_kind() {
+            TokenKind::In | TokenKind::Of => return self.for_in_stmt(start, left),
+            _ if is_decl => {}
+            got => self.report_expected(init_start, "in or of", got),
+        }
+
+        let init = self.var_decl_init(init_start, Pattern::Identifier(left), true);
 
         let test = {
             let start = self.cur_token.start;
@@ -256,6 +330,28 @@ impl<'a> Parser<'a> {
         )))
     }
 
+    /// for ((let)? identifier (in | of) expr) body
+    fn for_in_stmt(&mut self, start: usize, left: Identifier) -> Statement {
+        let is_of = self.cur_kind() == TokenKind::Of;
+        self.advance();
+
+        let right = {
+            let start = self.cur_token.start;
+            let right = self.expr();
+            self.eat_with_start(TokenKind::RParen, start);
+            right
+        };
+
+        let body = self.block_stmt();
+
+        Statement::ForInStatement(Box::new(uni_builder!(
+            self,
+            ForInStatement,
+            start,
+            [left, right, body, is_of]
+        )))
+    }
+
     // --------------- if statement -------------------
 
     fn if_stmt(&mut self) -> Statement {
@@ -288,6 +384,50 @@ impl<'a> Parser<'a> {
         ))
     }
 
+    /// try block (catch (identifier)? block)? (finally block)?
+    fn try_stmt(&mut self) -> Statement {
+        let start = self.cur_token.start;
+
+        self.eat(TokenKind::Try);
+
+        let block = self.block_stmt();
+
+        let mut param = None;
+        let mut handler = None;
+
+        if self.cur_kind() == TokenKind::Catch {
+            self.advance();
+
+            if self.cur_kind() == TokenKind::Identifier {
+                param = Some(Identifier {
+                    node: Node::new(self.cur_token.start, self.cur_token.end),
+                    name: format!("{}", self.cur_token.clone().value),
+                });
+                self.eat(TokenKind::Identifier);
+            }
+
+            handler = Some(self.block_stmt());
+        }
+
+        let mut finalizer = None;
+
+        if self.cur_kind() == TokenKind::Finally {
+            self.advance();
+            finalizer = Some(self.block_stmt());
+        }
+
+        if handler.is_none() && finalizer.is_none() {
+            self.report_expected(start, "catch or finally", self.cur_kind());
+        }
+
+        Statement::TryStatement(uni_builder!(
+            self,
+            TryStatement,
+            start,
+            [block, param, handler, finalizer]
+        ))
+    }
+
     // -------------- word statements -----------------
 
     fn return_stmt(&mut self) -> Statement {
@@ -295,6 +435,22 @@ impl<'a> Parser<'a> {
     }
 
     fn yield_stmt(&mut self) -> Statement {
+        match self.yields {
+            Some(_) if self.in_async => self.error(
+                "misplaced-yield",
+                "`yield` can't be used in an async function",
+                self.cur_token.start,
+                self.cur_token.end,
+            ),
+            Some(_) => self.yields = Some(true),
+            None => self.error(
+                "misplaced-yield",
+                "`yield` outside of a function",
+                self.cur_token.start,
+                self.cur_token.end,
+            ),
+        }
+
         word_stmt!(self, TokenKind::Yield, YieldStatement)
     }
 
@@ -325,6 +481,15 @@ impl<'a> Parser<'a> {
     // --------------- function declaration -----------------
 
     fn fn_decl(&mut self) -> Statement {
+        Statement::FunctionDeclaration(self.function(true))
+    }
+
+    /// fn[params] block | fn[params] expr, an expression body is returned
+    fn fn_expr(&mut self) -> Expression {
+        Expression::FunctionExpression(Box::new(self.function(false)))
+    }
+
+    fn function(&mut self, named: bool) -> FunctionDeclarator {
         let start = self.cur_token.start;
 
         let is_async = {
@@ -336,65 +501,119 @@ impl<'a> Parser<'a> {
             }
         };
 
-        self.advance();
+        self.eat(TokenKind::Function);
 
-        let id = format!("{}", self.cur_token.clone().value);
-        self.eat(TokenKind::Identifier);
+        let id = if named {
+            let id = format!("{}", self.cur_token.clone().value);
+            self.eat(TokenKind::Identifier);
+            id
+        } else {
+            String::new()
+        };
 
         let params = {
             let start = self.cur_token.start;
 
             self.eat(TokenKind::LSquare);
 
-            let params = self
-                .parse_params()
-                .into_iter()
-                .map(|p| format!("{}", p.value))
-                .collect();
+            let params = self.parse_params();
 
             self.eat_with_start(TokenKind::RSquare, start);
             params
         };
 
-        let body = self.block_stmt();
+        let outer_yields = self.yields.replace(false);
+        let outer_async = std::mem::replace(&mut self.in_async, is_async);
 
-        Statement::FunctionDeclaration(uni_builder!(
+        let body = if named || self.at(TokenKind::LAngle) {
+            self.block_stmt()
+        } else {
+            let start = self.cur_token.start;
+            let argument = self.ternary();
+
+            vec![Statement::ReturnStatement(ReturnStatement {
+                node: Node::new(start, self.prev_token_end),
+                argument,
+            })]
+        };
+
+        let is_generator = std::mem::replace(&mut self.yields, outer_yields) == Some(true);
+        self.in_async = outer_async;
+
+        uni_builder!(
             self,
             FunctionDeclarator,
             start,
-            [id, params, body, is_async]
-        ))
+            [id, params, body, is_async, is_generator]
+        )
     }
 
-    fn parse_params(&mut self) -> Vec<Token> {
+    fn parse_params(&mut self) -> Vec<Parameter> {
         let mut params = vec![];
 
         if self.cur_kind() == TokenKind::RSquare {
-            // self.advance();
             return params;
         }
 
-        params.push(self.cur_token.clone());
-        self.eat(TokenKind::Identifier);
+        params.push(self.param());
 
         while self.cur_kind() == TokenKind::Comma {
+            if params.last().unwrap().is_rest {
+                let node = params.last().unwrap().node;
+                self.error(
+                    "misplaced-rest-parameter",
+                    "Rest parameter must be the last one",
+                    node.start,
+                    node.end,
+                );
+            }
+
             self.advance();
-            params.push(self.cur_token.clone());
-            self.eat(TokenKind::Identifier);
+            params.push(self.param());
         }
 
         params
     }
 
+    /// ...identifier | identifier = expr | identifier
+    fn param(&mut self) -> Parameter {
+        let start = self.cur_token.start;
+
+        let is_rest = self.at(TokenKind::Ellipsis);
+        if is_rest {
+            self.advance();
+        }
+
+        let name = format!("{}", self.cur_token.value);
+        self.eat(TokenKind::Identifier);
+
+        let default = if !is_rest && self.at(TokenKind::Assign) {
+            self.advance();
+            Some(self.ternary())
+        } else {
+            None
+        };
+
+        Parameter {
+            node: Node::new(start, self.prev_token_end),
+            name,
+            default,
+            is_rest,
+        }
+    }
+
     // -------------- variable declaration -----------------
 
     fn var_decl(&mut self, only_with_init: bool) -> Statement {
         let start = self.cur_token.start;
         self.advance();
 
-        let id = format!("{}", self.cur_token.clone().value);
-        self.eat(TokenKind::Identifier);
+        let id = self.pattern(false);
 
+        self.var_decl_init(start, id, only_with_init)
+    }
+
+    fn var_decl_init(&mut self, start: usize, id: Pattern, only_with_init: bool) -> Statement {
         let mut is_formula = false;
 
         let init = {
@@ -406,16 +625,29 @@ impl<'a> Parser<'a> {
                     self.expr()
                 }
                 TokenKind::FormulaAssign => {
+                    if !matches!(id, Pattern::Identifier(_)) {
+                        self.error(
+                            "destructured-formula",
+                            "Formulas can't be destructured",
+                            start,
+                            self.cur_token.end,
+                        );
+                    }
+
                     is_formula = true;
                     self.advance();
                     self.expr()
                 }
-                _ if !only_with_init => Expression::None(None {
-                    node: Node::new(start, self.cur_token.end),
-                }),
+                _ if !only_with_init && matches!(id, Pattern::Identifier(_)) => {
+                    Expression::None(None {
+                        node: Node::new(start, self.cur_token.end),
+                    })
+                }
                 _ => {
                     self.report_expected(start, "Assign or FormulaAssign", self.cur_kind());
-                    unreachable!("Report ends proccess");
+                    Expression::None(None {
+                        node: Node::new(start, self.cur_token.end),
+                    })
                 }
             }
         };
@@ -430,21 +662,197 @@ impl<'a> Parser<'a> {
         ))
     }
 
+    /// identifier | _ | [pattern, ..., ...identifier] | {identifier (: pattern)?, ...}, `match` also takes literals and ranges
+    fn pattern(&mut self, refutable: bool) -> Pattern {
+        let start = self.cur_token.start;
+
+        match self.cur_kind() {
+            TokenKind::Number | TokenKind::Minus | TokenKind::Str if refutable => {
+                let literal = self.literal_pattern();
+
+                match (literal.value.as_f64(), self.cur_kind()) {
+                    (Some(start), TokenKind::Range) => {
+                        self.advance();
+
+                        let end = match self.literal_pattern().value {
+                            end if end.as_f64().is_some() => end.as_f64().unwrap(),
+                            got => {
+                                self.report_expected(literal.node.start, "Number", got);
+                                start
+                            }
+                        };
+
+                        Pattern::Range(Box::new(RangePattern {
+                            node: Node::new(literal.node.start, self.prev_token_end),
+                            start,
+                            end,
+                        }))
+                    }
+                    _ => Pattern::Literal(literal),
+                }
+            }
+            TokenKind::True | TokenKind::False | TokenKind::None if refutable => {
+                let value = match self.cur_kind() {
+                    TokenKind::True => TokenValue::Bool(true),
+                    TokenKind::False => TokenValue::Bool(false),
+                    _ => TokenValue::None,
+                };
+                self.advance();
+
+                Pattern::Literal(Literal {
+                    node: Node::new(start, self.prev_token_end),
+                    value,
+                })
+            }
+            TokenKind::LSquare => {
+                self.advance();
+
+                let mut elements = vec![];
+                let mut rest = None;
+
+                while !self.at(TokenKind::RSquare) {
+                    if self.at(TokenKind::Ellipsis) {
+                        self.advance();
+                        rest = Some(self.identifier());
+                        break;
+                    }
+
+                    elements.push(self.pattern(refutable));
+
+                    if !self.at(TokenKind::Comma) {
+                        break;
+                    }
+                    self.advance();
+                }
+
+                self.eat_with_start(TokenKind::RSquare, start);
+
+                Pattern::Sequence(Box::new(SequencePattern {
+                    node: Node::new(start, self.prev_token_end),
+                    elements,
+                    rest,
+                }))
+            }
+            TokenKind::LAngle => {
+                self.advance();
+
+                let mut entries = vec![];
+
+                while !self.at(TokenKind::RAngle) {
+                    let key = self.identifier();
+
+                    let value = if self.at(TokenKind::Colon) {
+                        self.advance();
+                        self.pattern(refutable)
+                    } else {
+                        Pattern::Identifier(key.clone())
+                    };
+
+                    entries.push((key, value));
+
+                    if !self.at(TokenKind::Comma) {
+                        break;
+                    }
+                    self.advance();
+                }
+
+                self.eat_with_start(TokenKind::RAngle, start);
+
+                Pattern::Hashmap(Box::new(HashmapPattern {
+                    node: Node::new(start, self.prev_token_end),
+                    entries,
+                }))
+            }
+            _ => {
+                let id = self.identifier();
+
+                match id.name.as_str() {
+                    "_" => Pattern::Wildcard(id.node),
+                    _ => Pattern::Identifier(id),
+                }
+            }
+        }
+    }
+
+    /// Number | -Number | String
+    fn literal_pattern(&mut self) -> Literal {
+        let start = self.cur_token.start;
+
+        let negative = self.at(TokenKind::Minus);
+        if negative {
+            self.advance();
+        }
+
+        let value = match (self.cur_token.value.clone(), self.cur_kind()) {
+            (TokenValue::Number(n), TokenKind::Number) if negative => TokenValue::Number(-n),
+            (TokenValue::Integer(i), TokenKind::Number) if negative => TokenValue::Integer(-i),
+            (TokenValue::Imaginary(n), TokenKind::Number) if negative => TokenValue::Imaginary(-n),
+            (value, TokenKind::Number) => value,
+            (value, TokenKind::Str) if !negative => value,
+            (_, got) => {
+                self.report_expected(start, "Number or String", got);
+                TokenValue::None
+            }
+        };
+        self.advance();
+
+        Literal {
+            node: Node::new(start, self.prev_token_end),
+            value,
+        }
+    }
+
+    fn identifier(&mut self) -> Identifier {
+        let token = self.cur_token.clone();
+        self.eat(TokenKind::Identifier);
+
+        Identifier {
+            node: Node::new(token.start, token.end),
+            name: format!("{}", token.value),
+        }
+    }
+
     // ---------------- assign statement -------------------
 
-    ///ternary (Assign | PlusAssign | MinusAssign | MultiplyAssign | DivideAssign | PowerAssign | ModuloAssign) ternary
+    ///identifier ([expr])* (Assign | PlusAssign | MinusAssign | MultiplyAssign | DivideAssign | PowerAssign | ModuloAssign) ternary | pattern Assign ternary
     fn assign_statement(&mut self) -> Statement {
         let start = self.cur_token.start;
 
         self.eat(TokenKind::Mut);
 
+        if self.at(TokenKind::LSquare) || self.at(TokenKind::LAngle) {
+            let left = self.pattern(false);
+            self.eat(TokenKind::Assign);
+
+            let indices = vec![];
+            let right = self.expr();
+            let operator = AssignOperator::Assign;
+            self.eat(TokenKind::Semicolon);
+
+            return Statement::AssignStatement(uni_builder!(
+                self,
+                AssignStatement,
+                start,
+                [left, indices, right, operator]
+            ));
+        }
+
         let left = self.cur_token.clone();
         self.eat(TokenKind::Identifier);
 
-        let left = Identifier {
+        let left = Pattern::Identifier(Identifier {
             node: Node::new(start, self.cur_token.end),
             name: format!("{}", left.value),
-        };
+        });
+
+        let mut indices = vec![];
+
+        while self.cur_kind() == TokenKind::LSquare {
+            let start = self.cur_token.start;
+            self.advance();
+            indices.push(self.expr());
+            self.eat_with_start(TokenKind::RSquare, start);
+        }
 
         if [
             TokenKind::Assign,
@@ -469,11 +877,14 @@ impl<'a> Parser<'a> {
                 self,
                 AssignStatement,
                 start,
-                [left, right, operator]
+                [left, indices, right, operator]
             ))
         } else {
             self.report_expected(start, "= | += | -= | *= | /= | ^= | %=", self.cur_kind());
-            unreachable!("Report ends proccess");
+
+            Statement::ExpressionStatement(Expression::None(None {
+                node: Node::new(start, self.cur_token.end),
+            }))
         }
     }
 
@@ -623,37 +1034,41 @@ impl<'a> Parser<'a> {
         match token.kind {
             TokenKind::Number | TokenKind::Str => {
                 self.advance();
-                return Expression::Literal(Literal {
+                Expression::Literal(Literal {
                     node: Node::new(token.start, token.end),
                     value: token.value,
-                });
+                })
             }
 
             TokenKind::True => {
                 self.advance();
-                return Expression::Literal(Literal {
+                Expression::Literal(Literal {
                     node: Node::new(token.start, token.end),
                     value: TokenValue::Bool(true),
-                });
+                })
             }
 
             TokenKind::False => {
                 self.advance();
-                return Expression::Literal(Literal {
+                Expression::Literal(Literal {
                     node: Node::new(token.start, token.end),
                     value: TokenValue::Bool(false),
-                });
+                })
             }
 
             TokenKind::LParen => {
                 self.advance();
                 let node = self.expr();
                 self.eat_with_start(TokenKind::RParen, token.start);
-                return node;
+                self.calls(token.start, node)
             }
 
             TokenKind::LSquare => self.read_seq_expr(token),
 
+            TokenKind::Function | TokenKind::Async => self.fn_expr(),
+
+            TokenKind::Match => self.match_expr(),
+
             TokenKind::ExclamationMark
             | TokenKind::PlusPlus
             | TokenKind::MinusMinus
@@ -663,21 +1078,62 @@ impl<'a> Parser<'a> {
                 self.advance();
 
                 let right = self.factor();
-                return self.unary_expression(token.start, token.kind, right);
+                self.unary_expression(token.start, token.kind, right)
             }
-            _ => return self.await_expr(),
+            _ => self.await_expr(),
         }
     }
 
-    fn read_seq_expr(&mut self, token: Token) -> Expression {
-        self.advance();
+    /// match expr { (pattern (if expr)? => expr),* }
+    fn match_expr(&mut self) -> Expression {
+        let start = self.cur_token.start;
+        self.eat(TokenKind::Match);
 
-        match self.cur_kind() {
-            TokenKind::RSquare => {
+        let discriminant = self.ternary();
+
+        let arms_start = self.cur_token.start;
+        self.eat(TokenKind::LAngle);
+
+        let mut arms = vec![];
+
+        while !self.at(TokenKind::RAngle) {
+            let start = self.cur_token.start;
+            let pattern = self.pattern(true);
+
+            let guard = if self.at(TokenKind::If) {
                 self.advance();
-                return self.sequence_expression(token.start, vec![]);
+                Some(self.ternary())
+            } else {
+                None
+            };
+
+            self.eat(TokenKind::Arrow);
+            let body = self.ternary();
+
+            arms.push(uni_builder!(self, MatchArm, start, [pattern, guard, body]));
+
+            if !self.at(TokenKind::Comma) {
+                break;
             }
-            _ => {}
+            self.advance();
+        }
+
+        self.eat_with_start(TokenKind::RAngle, arms_start);
+
+        Expression::MatchExpression(Box::new(uni_builder!(
+            self,
+            MatchExpression,
+            start,
+            [discriminant, arms]
+        )))
+    }
+
+    fn read_seq_expr(&mut self, token: Token) -> Expression {
+        self.advance();
+
+        if self.cur_kind() == TokenKind::RSquare {
+            self.advance();
+            return self.sequence_expression(token.start, vec![]);
         }
 
         let mut node = self.comma(true);
@@ -690,7 +1146,7 @@ impl<'a> Parser<'a> {
             _ => node = self.sequence_expression(token.start, vec![node]),
         }
 
-        return node;
+        node
     }
 
     /// await delete_expr | delete_expr
@@ -708,7 +1164,7 @@ impl<'a> Parser<'a> {
         member_left_associative!(self, [TokenKind::Dot], call)
     }
 
-    /// identifier[expr] | identifier
+    /// identifier[expr][expr]... | identifier
     fn call(&mut self) -> (Expression, bool) {
         let token = self.cur_token.clone();
 
@@ -716,56 +1172,12 @@ impl<'a> Parser<'a> {
             TokenKind::Identifier => {
                 self.advance();
 
-                match self.cur_kind() {
-                    TokenKind::LSquare => {
-                        let sequence_start = self.cur_token.start;
-
-                        self.advance();
-
-                        match self.cur_kind() {
-                            TokenKind::RSquare => {
-                                self.advance();
-
-                                let node = self.sequence_expression(sequence_start, vec![]);
-
-                                return (
-                                    self.call_expression(
-                                        sequence_start,
-                                        format!("{}", token.value),
-                                        node,
-                                    ),
-                                    false,
-                                );
-                            }
-                            _ => {}
-                        }
-
-                        let mut node = self.expr();
-                        self.eat_with_start(TokenKind::RSquare, token.start);
-
-                        match node {
-                            Expression::SequenceExpression(seq_exp) => {
-                                node =
-                                    self.sequence_expression(sequence_start, seq_exp.expressions);
-                            }
-                            _ => node = self.sequence_expression(sequence_start, vec![node]),
-                        }
+                let callee = Expression::Identifier(Identifier {
+                    node: Node::new(token.start, token.end),
+                    name: format!("{}", token.value),
+                });
 
-                        return (
-                            self.call_expression(token.start, format!("{}", token.value), node),
-                            false,
-                        );
-                    }
-                    _ => {
-                        return (
-                            Expression::Identifier(Identifier {
-                                node: Node::new(token.start, token.end),
-                                name: format!("{}", token.value),
-                            }),
-                            false,
-                        );
-                    }
-                }
+                (self.calls(token.start, callee), false)
             }
             TokenKind::LSquare => {
                 self.advance();
@@ -773,27 +1185,91 @@ impl<'a> Parser<'a> {
                 let node = self.expr();
                 self.eat_with_start(TokenKind::RSquare, token.start);
 
-                return (node, true);
+                (node, true)
             }
             got => {
                 self.report_expected(token.start, "Identifier or [", got);
-                unreachable!("Report ends proccess");
+
+                (
+                    Expression::None(None {
+                        node: Node::new(token.start, token.end),
+                    }),
+                    false,
+                )
             }
         }
     }
 
+    /// Calls the callee with each following `[args]`, so `f[1][2]` calls the result of `f[1]`
+    fn calls(&mut self, start: usize, callee: Expression) -> Expression {
+        let mut node = callee;
+
+        while self.at(TokenKind::LSquare) {
+            let sequence_start = self.cur_token.start;
+            self.advance();
+
+            let (arguments, named_arguments) = self.call_arguments(sequence_start);
+
+            node = self.call_expression(start, node, arguments, named_arguments);
+        }
+
+        node
+    }
+
+    /// Positional arguments as a sequence, so `f[[1, 2]]` passes one sequence, and then the named ones
+    fn call_arguments(&mut self, start: usize) -> (Expression, Vec<NamedArgument>) {
+        let mut arguments = vec![];
+        let mut named_arguments = vec![];
+
+        while !self.at(TokenKind::RSquare) && !self.recovering {
+            if !arguments.is_empty() || !named_arguments.is_empty() {
+                self.eat(TokenKind::Comma);
+            }
+
+            if self.at(TokenKind::Identifier) && self.peek_kind() == TokenKind::Assign {
+                let arg_start = self.cur_token.start;
+                let name = format!("{}", self.cur_token.value);
+                self.advance();
+                self.eat(TokenKind::Assign);
+
+                let value = self.ternary();
+
+                named_arguments.push(NamedArgument {
+                    node: Node::new(arg_start, self.prev_token_end),
+                    name,
+                    value,
+                });
+            } else if named_arguments.is_empty() {
+                arguments.push(self.ternary());
+            } else {
+                self.error(
+                    "misplaced-positional-argument",
+                    "Positional arguments must come before named arguments",
+                    self.cur_token.start,
+                    self.cur_token.end,
+                );
+            }
+        }
+
+        self.eat_with_start(TokenKind::RSquare, start);
+
+        (self.sequence_expression(start, arguments), named_arguments)
+    }
+
     // ------------------------------ Expression builders ------------------------------
 
     fn call_expression(
         &mut self,
         start: usize,
-        callee: String,
+        callee: Expression,
         arguments: Expression,
+        named_arguments: Vec<NamedArgument>,
     ) -> Expression {
         Expression::CallExpression(Box::new(CallExpression {
             node: Node::new(start, self.cur_token.end),
             callee,
             arguments,
+            named_arguments,
         }))
     }
 
@@ -943,6 +1419,7 @@ impl<'a> Parser<'a> {
         }
 
         let val = self.cur_token.value.to_string();
+        let recovering = self.recovering;
 
         self.report_expected(
             start,
@@ -957,29 +1434,75 @@ impl<'a> Parser<'a> {
                 }
             ),
         );
-        unreachable!("Report ends proccess");
+
+        // A missing `;` goes right after the previous token
+        if kind == TokenKind::Semicolon && !recovering {
+            let end = self.prev_token_end;
+            self.suggest("add the missing semicolon", end, end, ";");
+        }
+
+        false
     }
 
     fn report_expected<T: std::fmt::Display, U: std::fmt::Display>(
-        &self,
+        &mut self,
         start: usize,
         expected: T,
         got: U,
     ) {
-        report_error(
-            self.path,
-            self.source,
+        self.error(
+            "expected-token",
             &format!("Expected {expected} but got {got}"),
             start,
             self.cur_token.end,
         );
     }
 
+    /// Records a syntax error, the rest of the statement is skipped by `synchronize`
+    fn error(&mut self, code: &str, message: &str, start: usize, end: usize) {
+        if !self.recovering {
+            self.errors.push(ParseError {
+                node: Node::new(start, end),
+                message: message.to_owned(),
+                code: code.to_owned(),
+                fix: None,
+            });
+        }
+
+        self.recovering = true;
+    }
+
+    /// Attaches the fix to the error just recorded, errors skipped while recovering get none
+    fn suggest(&mut self, message: &str, start: usize, end: usize, replacement: &str) {
+        if let Some(error) = self.errors.last_mut() {
+            error.fix = Some(ParseFix {
+                node: Node::new(start, end),
+                message: message.to_owned(),
+                replacement: replacement.to_owned(),
+            });
+        }
+    }
+
     /// Move to the next token
     fn advance(&mut self) {
         self.prev_token_end = self.cur_token.end;
         let token = self.lexer.next_token();
         self.cur_token = token;
+
+        if self.at(TokenKind::Unexpected) {
+            self.error(
+                "unexpected-token",
+                "Unexpected token",
+                self.cur_token.start,
+                self.cur_token.end,
+            );
+            self.advance();
+        }
+    }
+
+    /// Kind of the token after the current one
+    fn peek_kind(&self) -> TokenKind {
+        self.lexer.clone().next_token().kind
     }
 
     fn cur_kind(&self) -> TokenKind {
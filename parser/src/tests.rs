@@ -8,7 +8,7 @@ pub mod expr_tests {
                 let test_str = $str;
                 let mut parser = Parser::new("test", test_str);
 
-                let ast = format!("{}", parser.parse());
+                let ast = format!("{}", parser.parse().unwrap());
                 assert_eq!(ast, format!("{};\n", $ast_str));
             }};
         }
@@ -54,4 +54,184 @@ pub mod expr_tests {
 
         assert_parser!("(a ? b : c) ? d : e;", "((a ? b : c) ? d : e)");
     }
+
+    #[test]
+    fn calls_round_trip() {
+        assert_parser!("ln[x];", "(ln[x])");
+        assert_parser!("f[[1, 2]];", "(f[[1, 2]])");
+
+        for source in [
+            "exp[2*x];",
+            "f[1, b = 2];",
+            "f[b = 2];",
+            "f[1][2];",
+            "f[[1], 2];",
+        ] {
+            let printed = format!("{}", Parser::new("test", source).parse().unwrap());
+            let reparsed = format!("{}", Parser::new("test", &printed).parse().unwrap());
+
+            assert_eq!(printed, reparsed);
+        }
+    }
+}
+
+pub mod stmt_tests {
+    use crate::parser::Parser;
+
+    #[macro_use]
+    mod utils {
+        macro_rules! assert_parser {
+            ($str: expr, $ast_str: expr) => {{
+                let test_str = $str;
+                let mut parser = Parser::new("test", test_str);
+
+                let ast = format!("{}", parser.parse().unwrap());
+                assert_eq!(ast, format!("{}\n", $ast_str));
+            }};
+        }
+    }
+
+    #[test]
+    fn for_in() {
+        assert_parser!("for (x in xs) x;", "for (x in xs) {\nx;\n}");
+        assert_parser!("for (let x in 1..3) x;", "for (x in (1..3)) {\nx;\n}");
+        assert_parser!("for (v of hm) { v; }", "for (v of hm) {\nv;\n}");
+    }
+
+    #[test]
+    fn assign_index() {
+        assert_parser!("mut a = 1;", "a = 1");
+        assert_parser!("mut a[0] = 1;", "a[0] = 1");
+        assert_parser!("mut a[0][i + 1] = 1;", "a[0][(i+1)] = 1");
+    }
+
+    #[test]
+    fn try_catch() {
+        assert_parser!(
+            "try { f[]; } catch e { e; }",
+            "try {\n(f[]);\n} catch e {\ne;\n}"
+        );
+        assert_parser!(
+            "try f[]; catch { 1; } finally { 2; }",
+            "try {\n(f[]);\n} catch {\n1;\n} finally {\n2;\n}"
+        );
+        assert_parser!(
+            "try { f[]; } finally 2;",
+            "try {\n(f[]);\n} finally {\n2;\n}"
+        );
+    }
+
+    #[test]
+    fn fn_expr() {
+        assert_parser!(
+            "let f = fn[x] x * 2;",
+            "let f = (fn (x) {\nreturn (x*2);\n});"
+        );
+        assert_parser!(
+            "map[fn[a, b] { return a; }];",
+            "(map[(fn (a, b) {\nreturn a;\n})]);"
+        );
+    }
+
+    #[test]
+    fn chained_calls() {
+        assert_parser!("f[1][2];", "((f[1])[2]);");
+        assert_parser!("(g)[];", "(g[]);");
+    }
+
+    #[test]
+    fn params() {
+        assert_parser!(
+            "fn f[a, b = 2, ...rest] a;",
+            "fn f(a, b = 2, ...rest) {\na;\n}"
+        );
+        assert_parser!("f[1, b = 2];", "(f[1, b = 2]);");
+    }
+
+    #[test]
+    fn destructuring() {
+        assert_parser!("let [a, [b], ...rest] = xs;", "let [a, [b], ...rest] = xs;");
+        assert_parser!("let {x, y} = point;", "let {x, y} = point;");
+        assert_parser!("mut [a, b] = [b, a];", "[a, b] = [b, a]");
+    }
+
+    #[test]
+    fn match_expr() {
+        assert_parser!(
+            "match x { 0 => a, 1..5 => b, [y, ...r] if y > 0 => y, {k: \"v\", z} => z, _ => c };",
+            "(match x {\n0 => a,\n1..5 => b,\n[y, ...r] if (y>0) => y,\n{k: \"v\", z} => z,\n_ => c\n});"
+        );
+    }
+}
+
+pub mod error_tests {
+    use crate::parser::Parser;
+
+    fn messages(source: &str) -> Vec<String> {
+        match Parser::new("test", source).parse() {
+            Ok(ast) => panic!("Expected syntax errors, got {ast}"),
+            Err(errors) => errors.into_iter().map(|error| error.message).collect(),
+        }
+    }
+
+    #[test]
+    fn errors_are_collected() {
+        assert_eq!(
+            messages("let a = ;\nlet b = 2;\nlet c = [1, 2;\nlet d = 1 $ 2;"),
+            [
+                "Expected Identifier or [ but got ;",
+                "Expected ] but got ; ",
+                "Unexpected token",
+            ]
+        );
+    }
+
+    #[test]
+    fn recovers_inside_blocks() {
+        assert_eq!(
+            messages("if (a) { let b = ); c; }\n}\nlet d = ;"),
+            [
+                "Expected Identifier or [ but got )",
+                "Expected statement but got }",
+                "Expected Identifier or [ but got ;",
+            ]
+        );
+    }
+
+    #[test]
+    fn one_error_per_statement() {
+        assert_eq!(
+            messages("f[1, ) + ];"),
+            ["Expected Identifier or [ but got )"]
+        );
+    }
+
+    #[test]
+    fn codes_and_fixes() {
+        let errors = Parser::new("test", "let a = 1\nlet b = 2;\nlet c = $;")
+            .parse()
+            .unwrap_err();
+
+        assert_eq!(errors[0].code, "expected-token");
+        let fix = errors[0].fix.as_ref().unwrap();
+        assert_eq!((fix.node.start, fix.node.end), (9, 9));
+        assert_eq!(fix.replacement, ";");
+
+        assert_eq!(errors[1].code, "unexpected-token");
+        assert_eq!(errors[1].fix, None);
+    }
+
+    #[test]
+    fn yield_in_async_function() {
+        assert_eq!(
+            messages(
+                "async fn f[] { yield 1; }
+let g = async fn[] { fn h[] { yield 2; } yield 3; };"
+            ),
+            [
+                "`yield` can't be used in an async function",
+                "`yield` can't be used in an async function",
+            ]
+        );
+    }
 }
@@ -1,41 +1,217 @@
-use crate::parser::*;
+use crate::{lexer::TokenValue, parser::*};
 use core::fmt;
-use std::{collections::HashMap, ops};
+use num_bigint::BigInt;
+use num_complex::Complex64;
+use num_rational::BigRational;
+use num_traits::{FromPrimitive, ToPrimitive, Zero};
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    ops,
+    rc::{Rc, Weak},
+};
 
 pub type Vault = HashMap<String, ScopeValue>;
 
 pub type Scope = HashMap<String, Value>;
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub enum Value {
+    #[default]
     None,
+
+    /// Float, produced by decimal literals like `0.1` and operations with them.
+    /// Decimal literals stay floats so `0.1 + 0.2` rounds like in other languages,
+    /// `rational[0.1]` gives the exact `1/10`
     Number(f64),
+
+    /// Integer of any size, produced by integer literals
+    Integer(BigInt),
+
+    /// Exact fraction like `1/3`, it is never a whole number
+    Rational(BigRational),
+
+    /// Complex number, produced by imaginary literals like `2i` and `complex[re, im]`
+    Complex(Complex64),
+
     Bool(bool),
     Str(String),
     Sequence(Vec<Value>),
+    Range(RangeValue),
 
     Ast(Expression),
     ScopeRef(String),
 
     NativeFunction(NativeFunction),
-    Function(FunctionDeclarator),
+    Function(Box<Closure>),
+
+    /// Suspended generator kept by the interpreter
+    Generator(Handle),
+
+    /// Value produced by an async function or a timer
+    Future(Handle),
+
+    Err(Box<ErrorValue>),
+}
+
+/// Error created by `error[kind, message, payload?]`
+#[derive(Clone, Debug)]
+pub struct ErrorValue {
+    /// Name to tell errors apart, like `ZeroDivision`
+    pub kind: String,
+    pub message: String,
+    pub payload: Value,
+
+    /// Span of the `throw`, `None` until the error is thrown
+    pub span: Option<Node>,
+
+    /// Index of the source file of the span
+    pub file: usize,
 
-    Err(String),
+    /// Functions being called when the error was thrown, innermost last
+    pub stack: Vec<CallFrame>,
 }
 
+/// Function value with the scopes it was declared in
+#[derive(Clone, Debug)]
+pub struct Closure {
+    pub declarator: FunctionDeclarator,
+
+    /// Scope stack at the declaration, the body is evaluated on top of it
+    pub env: Vec<Handle>,
+
+    /// Index of the source file the function is declared in
+    pub file: usize,
+}
+
+/// Id of something the interpreter keeps, like a scope or a generator, while there are handles to it
+#[derive(Clone, Debug)]
+pub struct Handle {
+    pub id: String,
+    pub refs: Rc<()>,
+}
+
+impl Handle {
+    pub fn new(id: String) -> Self {
+        Self {
+            id,
+            refs: Rc::new(()),
+        }
+    }
+
+    /// Reference that tells whether any clone of the handle is left
+    pub fn downgrade(&self) -> Weak<()> {
+        Rc::downgrade(&self.refs)
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct CallFrame {
+    pub name: String,
+
+    /// Call expression the function is called from
+    pub node: Node,
+
+    /// Index of the source file of the call expression
+    pub file: usize,
+}
+
+/// Line of a traceback
+#[derive(Clone, Debug, PartialEq)]
+pub struct TraceFrame {
+    /// Function the line is in, `<main>` for the top level of the program
+    pub function: String,
+    pub path: String,
+
+    /// Position of the span, both counted from 1
+    pub line: usize,
+    pub column: usize,
+
+    /// Source line of the span
+    pub code: String,
+}
+
+/// Error that stops the program, it is given back by `Interpreter::run` instead of exiting
+#[derive(Clone, Debug)]
+pub struct RuntimeError {
+    /// Stable identifier of the kind of the error, like `undefined-variable`
+    pub code: String,
+    pub message: String,
+
+    /// Span of the error in the source it happened in
+    pub node: Node,
+
+    /// Path and text of that source, errors in imported modules point into the module.
+    /// The text is shared with the interpreter instead of copied for every error
+    pub path: String,
+    pub source: Rc<str>,
+
+    /// Calls that led to the error, outermost first, the last line is the error itself
+    pub traceback: Vec<TraceFrame>,
+
+    /// Other spans of the same source with what they show, like the declaration of a variable
+    pub labels: Vec<(Node, String)>,
+    pub notes: Vec<String>,
+
+    /// Errors reported together with this one, like the other syntax errors of an imported module
+    pub related: Vec<RuntimeError>,
+}
+
+impl fmt::Display for RuntimeError {
+    /// The last line of the traceback is the error itself
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.traceback.last() {
+            Some(frame) => write!(
+                f,
+                "{}:{}:{}: {}",
+                frame.path, frame.line, frame.column, self.message
+            ),
+            None => write!(f, "{}: {}", self.path, self.message),
+        }
+    }
+}
+
+impl std::error::Error for RuntimeError {}
+
 #[derive(Clone, Debug)]
 pub enum ControlFlow {
     Continue,
     Break,
     Return(Value),
     Yield(Value),
+
+    /// Async task is suspended until the future with the id is resolved
+    Await(String),
     Throw(Value),
     None(Value),
+
+    /// Runtime error, it can't be caught and ends the program.
+    /// The error itself stays pending in the interpreter, it is taken once at the top level
+    Error,
+}
+
+impl ControlFlow {
+    /// Generators and async tasks keep their scopes when suspended
+    pub fn is_suspension(&self) -> bool {
+        matches!(self, ControlFlow::Yield(_) | ControlFlow::Await(_))
+    }
 }
 
 #[derive(Clone, Debug)]
 pub struct ScopeValue {
     pub values: Scope,
     pub named_scope_refs: Vec<String>,
+
+    /// Handles of the closures that see the scope, it is kept after it is left while there are any
+    pub closures: Weak<()>,
+
+    /// Declarations of the variables declared with `:=`, their formulas are evaluated each time they are read
+    pub formulas: HashMap<String, Node>,
+}
+
+impl Default for ScopeValue {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl ScopeValue {
@@ -43,8 +219,86 @@ impl ScopeValue {
         Self {
             values: HashMap::new(),
             named_scope_refs: vec![],
+            closures: Weak::new(),
+            formulas: HashMap::new(),
+        }
+    }
+
+    pub fn is_captured(&self) -> bool {
+        self.closures.strong_count() > 0
+    }
+
+    /// Handle for a closure that sees the scope
+    pub fn capture(&mut self, name: &str) -> Handle {
+        let refs = self.closures.upgrade().unwrap_or_else(|| {
+            let refs = Rc::new(());
+            self.closures = Rc::downgrade(&refs);
+            refs
+        });
+
+        Handle {
+            id: name.to_owned(),
+            refs,
+        }
+    }
+}
+
+/// Lazy arithmetic progression produced by `a..b` or `range[a, b, step]`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RangeValue {
+    pub start: f64,
+    pub end: f64,
+    pub step: f64,
+    pub inclusive: bool,
+}
+
+impl RangeValue {
+    /// Steps down if `end` is less than `start`
+    pub fn new(start: f64, end: f64, inclusive: bool) -> Self {
+        Self {
+            start,
+            end,
+            step: if end < start { -1.0 } else { 1.0 },
+            inclusive,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        let span = (self.end - self.start) / self.step;
+
+        if span.is_nan() || span < 0.0 {
+            return 0;
+        }
+
+        let steps = span.floor();
+
+        // Longer ranges than the address space are cut at `usize::MAX`
+        if !self.inclusive && steps == span {
+            steps as usize
+        } else {
+            (steps as usize).saturating_add(1)
         }
     }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Item at `index`, without bounds checking
+    pub fn nth(&self, index: usize) -> f64 {
+        self.start + index as f64 * self.step
+    }
+
+    pub fn contains(&self, n: f64) -> bool {
+        let index = (n - self.start) / self.step;
+
+        index.fract() == 0.0 && index >= 0.0 && (index as usize) < self.len()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = f64> {
+        let range = *self;
+        (0..range.len()).map(move |i| range.nth(i))
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -64,6 +318,89 @@ pub enum NativeFunction {
     HMValues,
     HMClear,
 
+    // Sequence
+    SeqLen,
+    SeqPush,
+    SeqPop,
+    SeqMap,
+    SeqFilter,
+    SeqReduce,
+    SeqSlice,
+    SeqJoin,
+    SeqSort,
+    SeqReverse,
+    SeqContains,
+
+    // Range
+    RangeNew,
+    RangeLen,
+    RangeContains,
+    RangeStep,
+    RangeToSequence,
+
+    // Generator
+    GenNext,
+    GenDone,
+    GenToSequence,
+
+    // Async
+    Sleep,
+
+    // Error
+    ErrNew,
+    ErrMessage,
+    ErrKind,
+    ErrPayload,
+    ErrSpan,
+    ErrRethrow,
+
+    // Algebra
+    AlgSimplify,
+    AlgExpand,
+    AlgSubstitute,
+    AlgEval,
+    AlgDiff,
+
+    // Number conversions
+    ToInt,
+    ToFloat,
+    ToRational,
+    ToComplex,
+
+    // Math
+    MathSqrt,
+    MathCbrt,
+    MathExp,
+    MathLn,
+    MathLog,
+    MathLog2,
+    MathLog10,
+    MathSin,
+    MathCos,
+    MathTan,
+    MathAsin,
+    MathAcos,
+    MathAtan,
+    MathAtan2,
+    MathSinh,
+    MathCosh,
+    MathTanh,
+    MathAbs,
+    MathFloor,
+    MathCeil,
+    MathRound,
+    MathTrunc,
+    MathSign,
+    MathMin,
+    MathMax,
+    MathHypot,
+    MathRandom,
+    MathRandomInt,
+    MathRe,
+    MathIm,
+    MathConj,
+    MathArg,
+
     // conversion methods
     ToString,
     IsError,
@@ -76,25 +413,64 @@ impl fmt::Display for Value {
         match self {
             Value::None => write!(f, "None"),
             Value::Number(n) => write!(f, "{}", n),
+            Value::Integer(i) => write!(f, "{}", i),
+            Value::Rational(r) => write!(f, "{}", r),
+            Value::Complex(c) if c.re == 0.0 => write!(f, "{}i", c.im),
+            Value::Complex(c) if c.im < 0.0 => write!(f, "{}-{}i", c.re, -c.im),
+            Value::Complex(c) => write!(f, "{}+{}i", c.re, c.im),
             Value::Bool(b) => write!(f, "{}", b),
             Value::Str(s) => write!(f, "{}", s),
-            Value::Sequence(_) => todo!(),
-            Value::Ast(_) => todo!(),
+            Value::Sequence(seq) => {
+                write!(f, "[")?;
+                for (i, val) in seq.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", val)?;
+                }
+                write!(f, "]")
+            }
+            Value::Range(r) => {
+                write!(
+                    f,
+                    "{}..{}{}",
+                    r.start,
+                    if r.inclusive { "" } else { "<" },
+                    r.end
+                )?;
+                if r.step.abs() != 1.0 {
+                    write!(f, " by {}", r.step)?;
+                }
+                Ok(())
+            }
+            Value::Ast(expr) => write!(f, "{}", expr),
             Value::ScopeRef(r) => write!(f, "{}", r),
-            Value::NativeFunction(_) => todo!(),
-            Value::Function(_) => todo!(),
-            Value::Err(e) => write!(f, "{}", e),
+            Value::NativeFunction(n) => write!(f, "<native fn {:?}>", n),
+            Value::Function(func) => write!(f, "<fn {}>", func.declarator.signature()),
+            Value::Generator(_) => write!(f, "<generator>"),
+            Value::Future(_) => write!(f, "<future>"),
+            Value::Err(e) => write!(f, "{}: {}", e.kind, e.message),
         }
     }
 }
 
 // ----------------- Math -----------------
 
+/// Numbers promoted to the narrowest kind that holds both: integers, then rationals, then floats, then complexes
+enum Numbers {
+    Integers(BigInt, BigInt),
+    Rationals(BigRational, BigRational),
+    Floats(f64, f64),
+    Complexes(Complex64, Complex64),
+}
+
 impl Value {
     pub fn as_bool(&self) -> bool {
         match self {
             Value::None => false,
             Value::Number(n) => *n != 0.0,
+            Value::Integer(i) => !i.is_zero(),
+            Value::Complex(c) => !c.is_zero(),
             Value::Bool(b) => *b,
             _ => true,
         }
@@ -122,67 +498,228 @@ impl Value {
     }
 
     pub fn bit_and(&self, other: &Value) -> Value {
-        Value::Number(match (self, other) {
-            (Value::Number(n1), Value::Number(n2)) => {
-                if n1.fract() != 0.0 || n2.fract() != 0.0 {
-                    return Value::None;
-                } else {
-                    ((*n1 as usize) & (*n2 as usize)) as f64
-                }
-            }
-            _ => 0.0,
-        })
+        self.bitwise(other, |a, b| a & b)
     }
 
     pub fn bit_or(&self, other: &Value) -> Value {
-        Value::Number(match (self, other) {
-            (Value::Number(n1), Value::Number(n2)) => {
-                if n1.fract() != 0.0 || n2.fract() != 0.0 {
-                    return Value::None;
-                } else {
-                    ((*n1 as usize) | (*n2 as usize)) as f64
-                }
-            }
-            _ => 0.0,
-        })
+        self.bitwise(other, |a, b| a | b)
     }
 
     pub fn bit_xor(&self, other: &Value) -> Value {
-        Value::Number(match (self, other) {
-            (Value::Number(n1), Value::Number(n2)) => {
-                if n1.fract() != 0.0 || n2.fract() != 0.0 {
-                    return Value::None;
-                } else {
-                    ((*n1 as usize) ^ (*n2 as usize)) as f64
-                }
-            }
-            _ => 0.0,
-        })
+        self.bitwise(other, |a, b| a ^ b)
+    }
+
+    /// Bitwise operations work on the whole parts of real numbers, `2.5 & 3` is `2 & 3`
+    fn bitwise(&self, other: &Value, op: impl Fn(&BigInt, &BigInt) -> BigInt) -> Value {
+        match (self.truncated(), other.truncated()) {
+            (Some(a), Some(b)) => Value::Integer(op(&a, &b)),
+            _ => Value::None,
+        }
     }
 
     pub fn pow(&self, other: &Value) -> Value {
-        match (self, other) {
-            (Value::Number(left), Value::Number(right)) => Value::Number(left.powf(*right)),
+        if let Some(value) = Value::symbolic(self, BinaryOperator::Power, other) {
+            return value;
+        }
+
+        if let Some(Numbers::Complexes(base, exponent)) = self.numbers(other) {
+            return Value::Complex(match other.to_integer().and_then(|n| n.to_i32()) {
+                Some(n) => base.powi(n),
+                None => base.powc(exponent),
+            });
+        }
+
+        // Integer exponents keep integers and rationals exact
+        if let Some(exponent) = match other {
+            Value::Integer(i) => i.to_i32(),
+            _ => None,
+        } {
+            match self {
+                Value::Integer(base) if exponent >= 0 => {
+                    return Value::Integer(base.pow(exponent as u32))
+                }
+                Value::Integer(base) if !base.is_zero() => {
+                    return Value::rational(BigRational::from_integer(base.clone()).pow(exponent))
+                }
+                Value::Rational(base) => return Value::rational(base.pow(exponent)),
+                _ => {}
+            }
+        }
+
+        match (self.as_f64(), other.as_f64()) {
+            (Some(left), Some(right)) => Value::Number(left.powf(right)),
             _ => Value::None,
         }
     }
 
-    pub fn range(&self, other: &Value) -> Value {
-        match (self, other) {
-            (Value::Number(left), Value::Number(right)) => {
-                let left = left.round() as usize;
-                let right = right.round() as usize;
+    /// Number as a float, `None` if it is not a number
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Number(n) => Some(*n),
+            Value::Integer(i) => Some(i.to_f64().unwrap_or(f64::NAN)),
+            Value::Rational(r) => Some(r.to_f64().unwrap_or(f64::NAN)),
+            _ => None,
+        }
+    }
+
+    /// Number as a complex number, `None` if it is not a number
+    pub fn to_complex(&self) -> Option<Complex64> {
+        match self {
+            Value::Complex(c) => Some(*c),
+            _ => Some(Complex64::new(self.as_f64()?, 0.0)),
+        }
+    }
+
+    pub fn is_number(&self) -> bool {
+        self.as_f64().is_some()
+    }
+
+    /// Whole number as an integer, floats are accepted if they have no fraction
+    pub fn to_integer(&self) -> Option<BigInt> {
+        match self {
+            Value::Integer(i) => Some(i.clone()),
+            Value::Number(n) if n.fract() == 0.0 => BigInt::from_f64(*n),
+            _ => None,
+        }
+    }
 
-                let val = (left..=right).collect::<Vec<usize>>();
-                Value::Sequence(val.into_iter().map(|p| Value::Number(p as f64)).collect())
+    /// Whole part of a real number, rounded toward zero
+    pub fn truncated(&self) -> Option<BigInt> {
+        match self {
+            Value::Integer(i) => Some(i.clone()),
+            Value::Rational(r) => Some(r.to_integer()),
+            Value::Number(n) => BigInt::from_f64(n.trunc()),
+            _ => None,
+        }
+    }
+
+    /// Exact number as a fraction, `None` for floats
+    pub fn to_rational(&self) -> Option<BigRational> {
+        match self {
+            Value::Integer(i) => Some(BigRational::from_integer(i.clone())),
+            Value::Rational(r) => Some(r.clone()),
+            _ => None,
+        }
+    }
+
+    /// Value of the fraction, whole numbers become integers
+    pub fn rational(r: BigRational) -> Value {
+        if r.is_integer() {
+            Value::Integer(r.to_integer())
+        } else {
+            Value::Rational(r)
+        }
+    }
+
+    /// Float as an integer if it has no fraction, like items of ranges
+    pub fn integral(n: f64) -> Value {
+        match BigInt::from_f64(n) {
+            Some(i) if n.fract() == 0.0 => Value::Integer(i),
+            _ => Value::Number(n),
+        }
+    }
+
+    /// Operands of an arithmetic operation promoted to the same kind of number
+    fn numbers(&self, other: &Value) -> Option<Numbers> {
+        if matches!(
+            (self, other),
+            (Value::Complex(_), _) | (_, Value::Complex(_))
+        ) {
+            return Some(Numbers::Complexes(self.to_complex()?, other.to_complex()?));
+        }
+
+        if let (Value::Integer(a), Value::Integer(b)) = (self, other) {
+            return Some(Numbers::Integers(a.clone(), b.clone()));
+        }
+
+        match (self.to_rational(), other.to_rational()) {
+            (Some(a), Some(b)) => Some(Numbers::Rationals(a, b)),
+            _ => Some(Numbers::Floats(self.as_f64()?, other.as_f64()?)),
+        }
+    }
+
+    /// Order of two numbers, `None` if one of them is not a number or is complex
+    pub fn compare(&self, other: &Value) -> Option<Ordering> {
+        match self.numbers(other)? {
+            Numbers::Integers(a, b) => Some(a.cmp(&b)),
+            Numbers::Rationals(a, b) => Some(a.cmp(&b)),
+            Numbers::Floats(a, b) => a.partial_cmp(&b),
+            Numbers::Complexes(..) => None,
+        }
+    }
+
+    /// Expression of a number or a formula
+    pub fn to_expression(&self) -> Option<Expression> {
+        match self {
+            Value::Number(n) => Some(Expression::Literal(Literal {
+                node: Node::new(0, 0),
+                value: TokenValue::Number(*n),
+            })),
+            Value::Integer(i) => Some(Expression::Literal(Literal {
+                node: Node::new(0, 0),
+                value: TokenValue::Integer(i.clone()),
+            })),
+            Value::Rational(r) => Some(Expression::BinaryExpression(Box::new(BinaryExpression {
+                node: Node::new(0, 0),
+                left: Value::Integer(r.numer().clone()).to_expression()?,
+                operator: BinaryOperator::Divide,
+                right: Value::Integer(r.denom().clone()).to_expression()?,
+            }))),
+            Value::Complex(c) => {
+                let im = Expression::Literal(Literal {
+                    node: Node::new(0, 0),
+                    value: TokenValue::Imaginary(c.im),
+                });
+
+                if c.re == 0.0 {
+                    return Some(im);
+                }
+
+                Some(Expression::BinaryExpression(Box::new(BinaryExpression {
+                    node: Node::new(0, 0),
+                    left: Value::Number(c.re).to_expression()?,
+                    operator: BinaryOperator::Add,
+                    right: im,
+                })))
             }
+            Value::Ast(expr) => Some(expr.clone()),
+            _ => None,
+        }
+    }
+
+    /// Arithmetic with a formula builds a new formula
+    fn symbolic(left: &Value, operator: BinaryOperator, right: &Value) -> Option<Value> {
+        if !matches!((left, right), (Value::Ast(_), _) | (_, Value::Ast(_))) {
+            return None;
+        }
+
+        Some(Value::Ast(Expression::BinaryExpression(Box::new(
+            BinaryExpression {
+                node: Node::new(0, 0),
+                left: left.to_expression()?,
+                operator,
+                right: right.to_expression()?,
+            },
+        ))))
+    }
+
+    pub fn range(&self, other: &Value) -> Value {
+        match (self.as_f64(), other.as_f64()) {
+            (Some(left), Some(right)) => Value::Range(RangeValue::new(left, right, true)),
             _ => Value::None,
         }
     }
 
     pub fn equal(&self, other: &Value) -> Value {
+        if let Some(Numbers::Complexes(a, b)) = self.numbers(other) {
+            return Value::Bool(a == b);
+        }
+
+        if self.is_number() && other.is_number() {
+            return Value::Bool(self.compare(other) == Some(Ordering::Equal));
+        }
+
         Value::Bool(match (self, other) {
-            (Value::Number(n1), Value::Number(n2)) => n1 == n2,
             (Value::Bool(b1), Value::Bool(b2)) => b1 == b2,
             (Value::Str(s1), Value::Str(s2)) => s1 == s2,
             // (Value::Ast(e1), Value::Ast(e2)) => e1 == e2,
@@ -190,41 +727,38 @@ impl Value {
             // (Value::Function(f1), Value::Function(f2)) => f1 == f2,
             // (Value::NativeFunction(n1), Value::NativeFunction(n2)) => n1 == n2,
             (Value::None, Value::None) => true,
-            // (Value::Sequence(s1), Value::Sequence(s2)) => s1 == s2,
+            (Value::Range(r1), Value::Range(r2)) => r1 == r2,
+            (Value::Sequence(s1), Value::Sequence(s2)) => {
+                s1.len() == s2.len() && s1.iter().zip(s2).all(|(a, b)| a.equal(b).as_bool())
+            }
             _ => false,
         })
     }
 
     pub fn not_equal(&self, other: &Value) -> Value {
-        return !self.equal(other);
+        !self.equal(other)
     }
 
     pub fn greater(&self, other: &Value) -> Value {
-        Value::Bool(match (self, other) {
-            (Value::Number(n1), Value::Number(n2)) => n1 > n2,
-            _ => false,
-        })
+        Value::Bool(matches!(self.compare(other), Some(Ordering::Greater)))
     }
 
     pub fn greater_equal(&self, other: &Value) -> Value {
-        Value::Bool(match (self, other) {
-            (Value::Number(n1), Value::Number(n2)) => n1 >= n2,
-            _ => false,
-        })
+        Value::Bool(matches!(
+            self.compare(other),
+            Some(Ordering::Greater | Ordering::Equal)
+        ))
     }
 
     pub fn less(&self, other: &Value) -> Value {
-        Value::Bool(match (self, other) {
-            (Value::Number(n1), Value::Number(n2)) => n1 < n2,
-            _ => false,
-        })
+        Value::Bool(matches!(self.compare(other), Some(Ordering::Less)))
     }
 
     pub fn less_equal(&self, other: &Value) -> Value {
-        Value::Bool(match (self, other) {
-            (Value::Number(n1), Value::Number(n2)) => n1 <= n2,
-            _ => false,
-        })
+        Value::Bool(matches!(
+            self.compare(other),
+            Some(Ordering::Less | Ordering::Equal)
+        ))
     }
 }
 
@@ -232,10 +766,22 @@ impl ops::Add for Value {
     type Output = Value;
 
     fn add(self, rhs: Self) -> Self::Output {
+        if let Some(value) = Value::symbolic(&self, BinaryOperator::Add, &rhs) {
+            return value;
+        }
+
+        if let Some(numbers) = self.numbers(&rhs) {
+            return match numbers {
+                Numbers::Integers(a, b) => Value::Integer(a + b),
+                Numbers::Rationals(a, b) => Value::rational(a + b),
+                Numbers::Floats(a, b) => Value::Number(a + b),
+                Numbers::Complexes(a, b) => Value::Complex(a + b),
+            };
+        }
+
         match (self, rhs) {
-            (Value::Number(n1), Value::Number(n2)) => Value::Number(n1 + n2),
-            (Value::Number(n), Value::Str(str)) => Value::Str(format!("{}{}", n, str)),
-            (Value::Str(str), Value::Number(n)) => Value::Str(format!("{}{}", str, n)),
+            (n, Value::Str(str)) if n.is_number() => Value::Str(format!("{}{}", n, str)),
+            (Value::Str(str), n) if n.is_number() => Value::Str(format!("{}{}", str, n)),
             (Value::Str(str1), Value::Str(str2)) => Value::Str(str1 + &str2),
             (Value::Bool(b1), Value::Bool(b2)) => Value::Bool(b1 || b2),
 
@@ -248,10 +794,16 @@ impl ops::Sub for Value {
     type Output = Value;
 
     fn sub(self, rhs: Self) -> Self::Output {
-        match (self, rhs) {
-            (Value::Number(n1), Value::Number(n2)) => Value::Number(n1 - n2),
+        if let Some(value) = Value::symbolic(&self, BinaryOperator::Substract, &rhs) {
+            return value;
+        }
 
-            _ => Value::None,
+        match self.numbers(&rhs) {
+            Some(Numbers::Integers(a, b)) => Value::Integer(a - b),
+            Some(Numbers::Rationals(a, b)) => Value::rational(a - b),
+            Some(Numbers::Floats(a, b)) => Value::Number(a - b),
+            Some(Numbers::Complexes(a, b)) => Value::Complex(a - b),
+            None => Value::None,
         }
     }
 }
@@ -260,10 +812,23 @@ impl ops::Mul for Value {
     type Output = Value;
 
     fn mul(self, rhs: Self) -> Self::Output {
+        if let Some(value) = Value::symbolic(&self, BinaryOperator::Multiply, &rhs) {
+            return value;
+        }
+
+        if let Some(numbers) = self.numbers(&rhs) {
+            return match numbers {
+                Numbers::Integers(a, b) => Value::Integer(a * b),
+                Numbers::Rationals(a, b) => Value::rational(a * b),
+                Numbers::Floats(a, b) => Value::Number(a * b),
+                Numbers::Complexes(a, b) => Value::Complex(a * b),
+            };
+        }
+
         match (self, rhs) {
-            (Value::Number(n1), Value::Number(n2)) => Value::Number(n1 * n2),
-            (Value::Str(str), Value::Number(n)) => Value::Str(str.repeat(n as usize)),
-            (Value::Number(n), Value::Str(str)) => Value::Str(str.repeat(n as usize)),
+            (Value::Str(str), n) | (n, Value::Str(str)) if n.is_number() => {
+                Value::Str(str.repeat(n.as_f64().unwrap() as usize))
+            }
             (Value::Bool(b1), Value::Bool(b2)) => Value::Bool(b1 && b2),
 
             _ => Value::None,
@@ -275,9 +840,19 @@ impl ops::Div for Value {
     type Output = Value;
 
     fn div(self, rhs: Self) -> Self::Output {
-        match (self, rhs) {
-            (Value::Number(n1), Value::Number(n2)) => Value::Number(n1 / n2),
-            _ => Value::None,
+        if let Some(value) = Value::symbolic(&self, BinaryOperator::Divide, &rhs) {
+            return value;
+        }
+
+        // Division of integers is exact, by zero it gives an infinity like floats
+        match self.numbers(&rhs) {
+            Some(Numbers::Integers(a, b)) if !b.is_zero() => {
+                Value::rational(BigRational::new(a, b))
+            }
+            Some(Numbers::Rationals(a, b)) if !b.is_zero() => Value::rational(a / b),
+            Some(Numbers::Complexes(a, b)) => Value::Complex(a / b),
+            Some(_) => Value::Number(self.as_f64().unwrap() / rhs.as_f64().unwrap()),
+            None => Value::None,
         }
     }
 }
@@ -286,9 +861,12 @@ impl ops::Rem for Value {
     type Output = Value;
 
     fn rem(self, rhs: Self) -> Self::Output {
-        match (self, rhs) {
-            (Value::Number(n1), Value::Number(n2)) => Value::Number(n1 % n2),
-            _ => Value::None,
+        match self.numbers(&rhs) {
+            Some(Numbers::Integers(a, b)) if !b.is_zero() => Value::Integer(a % b),
+            Some(Numbers::Rationals(a, b)) if !b.is_zero() => Value::rational(a % b),
+            Some(Numbers::Complexes(..)) => Value::None,
+            Some(_) => Value::Number(self.as_f64().unwrap() % rhs.as_f64().unwrap()),
+            None => Value::None,
         }
     }
 }
@@ -299,6 +877,16 @@ impl ops::Neg for Value {
     fn neg(self) -> Self::Output {
         match self {
             Value::Number(n) => Value::Number(-n),
+            Value::Integer(i) => Value::Integer(-i),
+            Value::Rational(r) => Value::Rational(-r),
+            Value::Complex(c) => Value::Complex(-c),
+            Value::Ast(expr) => {
+                Value::Ast(Expression::UnaryExpression(Box::new(UnaryExpression {
+                    node: Node::new(0, 0),
+                    operator: UnaryOperator::Minus,
+                    right: expr,
+                })))
+            }
             _ => Value::None,
         }
     }
@@ -310,21 +898,30 @@ impl ops::Not for Value {
     fn not(self) -> Self::Output {
         match self {
             Value::None => Value::None,
-            Value::Number(n) => Value::Bool(if n == 0.0 { true } else { false }),
+            Value::Number(n) => Value::Bool(n == 0.0),
+            Value::Integer(i) => Value::Bool(i.is_zero()),
+            Value::Complex(c) => Value::Bool(c.is_zero()),
             Value::Bool(b) => Value::Bool(!b),
             _ => Value::Bool(false),
         }
     }
 }
 
+/// Largest amount integers are shifted left by, larger shifts would take all the memory
+pub const MAX_SHIFT: usize = 1 << 24;
+
 impl ops::Shl for Value {
     type Output = Value;
 
+    /// `None` for shifts larger than [`MAX_SHIFT`]
     fn shl(self, rhs: Self) -> Self::Output {
-        match (self, rhs) {
-            (Value::Number(n1), Value::Number(n2)) => {
-                Value::Number(((n1 as usize) << n2 as usize) as f64)
-            }
+        let shift = rhs
+            .truncated()
+            .and_then(|n| n.to_usize())
+            .filter(|&shift| shift <= MAX_SHIFT);
+
+        match (self.truncated(), shift) {
+            (Some(n), Some(shift)) => Value::Integer(n << shift),
             _ => Value::None,
         }
     }
@@ -334,10 +931,8 @@ impl ops::Shr for Value {
     type Output = Value;
 
     fn shr(self, rhs: Self) -> Self::Output {
-        match (self, rhs) {
-            (Value::Number(n1), Value::Number(n2)) => {
-                Value::Number(((n1 as usize) >> n2 as usize) as f64)
-            }
+        match (self.truncated(), rhs.truncated().and_then(|n| n.to_usize())) {
+            (Some(n), Some(shift)) => Value::Integer(n >> shift),
             _ => Value::None,
         }
     }
@@ -1,5 +1,8 @@
 use std::{fmt, ops};
 
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_traits::{ToPrimitive, Zero};
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -59,6 +62,9 @@ pub enum TokenKind {
     Range,
     Modulo,
 
+    /// Rest parameter prefix
+    Ellipsis,
+
     // Bitwise operators (Keyword2Operator)
     Ampersand,
     Pipe,
@@ -82,6 +88,9 @@ pub enum TokenKind {
     // Unary logic operators
     ExclamationMark,
 
+    /// `=>` of match arms
+    Arrow,
+
     /// Assignments operators (+=, -=, *=, /=...)
     Assign,
     FormulaAssign,
@@ -140,6 +149,10 @@ pub enum TokenKind {
     Of,
     Delete,
     Throw,
+    Try,
+    Catch,
+    Finally,
+    Match,
 
     Mut,
 
@@ -176,6 +189,7 @@ impl fmt::Display for TokenKind {
             TokenKind::Caret => write!(f, "^"),
             TokenKind::Range => write!(f, ".."),
             TokenKind::Modulo => write!(f, "%"),
+            TokenKind::Ellipsis => write!(f, "..."),
 
             TokenKind::Ampersand => write!(f, "&"),
             TokenKind::Pipe => write!(f, "|"),
@@ -193,6 +207,7 @@ impl fmt::Display for TokenKind {
             TokenKind::Xor => write!(f, "xor"),
             TokenKind::ExclamationMark => write!(f, "!"),
 
+            TokenKind::Arrow => write!(f, "=>"),
             TokenKind::Assign => write!(f, "="),
             TokenKind::FormulaAssign => write!(f, ":="),
             TokenKind::PlusAssign => write!(f, "+="),
@@ -242,6 +257,10 @@ impl fmt::Display for TokenKind {
             TokenKind::Of => write!(f, "of"),
             TokenKind::Delete => write!(f, "delete"),
             TokenKind::Throw => write!(f, "throw"),
+            TokenKind::Try => write!(f, "try"),
+            TokenKind::Catch => write!(f, "catch"),
+            TokenKind::Finally => write!(f, "finally"),
+            TokenKind::Match => write!(f, "match"),
 
             TokenKind::Import => write!(f, "import"),
             TokenKind::As => write!(f, "as"),
@@ -259,7 +278,16 @@ impl fmt::Display for TokenKind {
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum TokenValue {
     None,
+
+    /// Float literal like `1.5` or `1e3`
     Number(f64),
+
+    /// Integer literal, it is not limited in size
+    Integer(BigInt),
+
+    /// Imaginary literal like `2i`
+    Imaginary(f64),
+
     Str(String),
     Identifier(String),
     Bool(bool),
@@ -279,7 +307,7 @@ mod math {
                         kind: self.kind,
                         start: self.start,
                         end: rhs.end,
-                        value: self.value + rhs.value,
+                        value: <TokenValue as $Op>::$fn(self.value, rhs.value),
                     }
                 }
             }
@@ -292,6 +320,8 @@ impl fmt::Display for TokenValue {
         match self {
             TokenValue::None => write!(f, ""),
             TokenValue::Number(s) => write!(f, "{}", s),
+            TokenValue::Integer(i) => write!(f, "{}", i),
+            TokenValue::Imaginary(n) => write!(f, "{}i", n),
             TokenValue::Str(s) => write!(f, "\"{}\"", s),
             TokenValue::Identifier(s) => write!(f, "{}", s),
             TokenValue::Bool(b) => write!(f, "{}", b),
@@ -299,6 +329,17 @@ impl fmt::Display for TokenValue {
     }
 }
 
+impl TokenValue {
+    /// Value of a float or an integer literal as a float
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            TokenValue::Number(n) => Some(*n),
+            TokenValue::Integer(i) => i.to_f64(),
+            _ => None,
+        }
+    }
+}
+
 token_math!(ops::Add, add);
 token_math!(ops::Sub, sub);
 token_math!(ops::Mul, mul);
@@ -309,8 +350,21 @@ impl ops::Add for TokenValue {
 
     fn add(self, rhs: Self) -> Self::Output {
         match (self, rhs) {
+            (TokenValue::Integer(i1), TokenValue::Integer(i2)) => TokenValue::Integer(i1 + i2),
+            (TokenValue::Integer(i), TokenValue::Number(n))
+            | (TokenValue::Number(n), TokenValue::Integer(i)) => {
+                TokenValue::Number(n + i.to_f64().unwrap_or(f64::NAN))
+            }
             (TokenValue::Number(n1), TokenValue::Number(n2)) => TokenValue::Number(n1 + n2),
 
+            (TokenValue::Integer(i), TokenValue::Str(str)) => {
+                TokenValue::Str(format!("{}{}", i, str))
+            }
+
+            (TokenValue::Str(str), TokenValue::Integer(i)) => {
+                TokenValue::Str(format!("{}{}", str, i))
+            }
+
             (TokenValue::Number(n), TokenValue::Str(str)) => {
                 TokenValue::Str(format!("{}{}", n, str))
             }
@@ -329,6 +383,8 @@ impl ops::Add for TokenValue {
             (TokenValue::Identifier(_), _) | (_, TokenValue::Identifier(_)) => {
                 panic!("Identifiers can't be added")
             }
+
+            _ => TokenValue::None,
         }
     }
 }
@@ -338,7 +394,10 @@ impl ops::Sub for TokenValue {
 
     fn sub(self, rhs: Self) -> Self::Output {
         match (self, rhs) {
-            (TokenValue::Number(n1), TokenValue::Number(n2)) => TokenValue::Number(n1 - n2),
+            (TokenValue::Integer(i1), TokenValue::Integer(i2)) => TokenValue::Integer(i1 - i2),
+            (v1, v2) if v1.as_f64().is_some() && v2.as_f64().is_some() => {
+                TokenValue::Number(v1.as_f64().unwrap() - v2.as_f64().unwrap())
+            }
 
             (TokenValue::Identifier(_), _) | (_, TokenValue::Identifier(_)) => {
                 panic!("Identifiers can't be subtracted")
@@ -354,7 +413,15 @@ impl ops::Mul for TokenValue {
 
     fn mul(self, rhs: Self) -> Self::Output {
         match (self, rhs) {
-            (TokenValue::Number(n1), TokenValue::Number(n2)) => TokenValue::Number(n1 * n2),
+            (TokenValue::Integer(i1), TokenValue::Integer(i2)) => TokenValue::Integer(i1 * i2),
+            (v1, v2) if v1.as_f64().is_some() && v2.as_f64().is_some() => {
+                TokenValue::Number(v1.as_f64().unwrap() * v2.as_f64().unwrap())
+            }
+
+            (TokenValue::Str(str), TokenValue::Integer(i))
+            | (TokenValue::Integer(i), TokenValue::Str(str)) => {
+                TokenValue::Str(str.repeat(i.to_usize().unwrap_or(0)))
+            }
 
             (TokenValue::Str(str), TokenValue::Number(n)) => {
                 TokenValue::Str(str.repeat(n as usize))
@@ -378,7 +445,15 @@ impl ops::Div for TokenValue {
 
     fn div(self, rhs: Self) -> Self::Output {
         match (self, rhs) {
-            (TokenValue::Number(n1), TokenValue::Number(n2)) => TokenValue::Number(n1 / n2),
+            // Literals have no fractions, so only exact integer quotients stay integers
+            (TokenValue::Integer(i1), TokenValue::Integer(i2))
+                if !i2.is_zero() && i1.is_multiple_of(&i2) =>
+            {
+                TokenValue::Integer(i1 / i2)
+            }
+            (v1, v2) if v1.as_f64().is_some() && v2.as_f64().is_some() => {
+                TokenValue::Number(v1.as_f64().unwrap() / v2.as_f64().unwrap())
+            }
 
             (TokenValue::Identifier(_), _) | (_, TokenValue::Identifier(_)) => {
                 panic!("Identifiers can't be divided")
@@ -30,6 +30,27 @@ impl Node {
     }
 }
 
+/// Syntax error, the parser collects them and goes on from the next statement
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ParseError {
+    pub node: Node,
+    pub message: String,
+
+    /// Stable identifier of the kind of the error, like `expected-token`
+    pub code: String,
+
+    /// Edit that fixes the error when it is known, like adding a missing `;`
+    pub fix: Option<ParseFix>,
+}
+
+/// Replacement of the span of the node
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ParseFix {
+    pub node: Node,
+    pub message: String,
+    pub replacement: String,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Property {
     pub node: Node,
@@ -50,7 +71,9 @@ pub enum Statement {
     ScopeDeclaration(ScopeDeclarator),
     ContextDeclaration(ContextDeclarator),
     IfStatement(IfStatement),
+    TryStatement(TryStatement),
     ForStatement(Box<ForStatement>),
+    ForInStatement(Box<ForInStatement>),
     WhileStatement(WhileStatement),
     LoopStatement(LoopStatement),
     BlockStatement(BlockStatement),
@@ -68,7 +91,12 @@ pub struct ImportStatement {
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct AssignStatement {
     pub node: Node,
-    pub left: Identifier,
+
+    /// Patterns other than identifiers are only assigned with `=`
+    pub left: Pattern,
+
+    /// Indices of the assigned item in `left` sequence (`mut xs[0][1] = 2;`)
+    pub indices: Vec<Expression>,
     pub right: Expression,
     pub operator: AssignOperator,
 }
@@ -95,6 +123,17 @@ pub struct ForStatement {
     pub body: BlockStatement,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ForInStatement {
+    pub node: Node,
+    pub left: Identifier,
+    pub right: Expression,
+    pub body: BlockStatement,
+
+    /// `for (x of y)` walks hashmap values instead of keys
+    pub is_of: bool,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ReturnStatement {
     pub node: Node,
@@ -116,7 +155,7 @@ pub struct YieldStatement {
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct VariableDeclarator {
     pub node: Node,
-    pub id: String,
+    pub id: Pattern,
     pub init: Expression,
     pub is_formula: bool,
 }
@@ -125,9 +164,31 @@ pub struct VariableDeclarator {
 pub struct FunctionDeclarator {
     pub node: Node,
     pub id: String,
-    pub params: Vec<String>,
+    pub params: Vec<Parameter>,
     pub body: BlockStatement,
     pub is_async: bool,
+
+    /// Functions with a `yield` return a generator when called
+    pub is_generator: bool,
+}
+
+impl FunctionDeclarator {
+    /// Name and parameters like `f[a, b = 2, ...rest]`, used in errors
+    pub fn signature(&self) -> String {
+        format!("{}[{}]", self.id, format_vec(&self.params, ", "))
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Parameter {
+    pub node: Node,
+    pub name: String,
+
+    /// Evaluated in the scope of the call when the argument is not given
+    pub default: Option<Expression>,
+
+    /// `...rest` collects the positional arguments left into a sequence
+    pub is_rest: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -152,6 +213,78 @@ pub struct IfStatement {
     pub alternate: BlockStatement,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TryStatement {
+    pub node: Node,
+    pub block: BlockStatement,
+
+    /// Variable the caught value is bound to (`catch e`)
+    pub param: Option<Identifier>,
+    pub handler: Option<BlockStatement>,
+    pub finalizer: Option<BlockStatement>,
+}
+
+/// Target of `let` and `mut` that unpacks the value into variables, also checked against values by `match`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub enum Pattern {
+    Identifier(Identifier),
+
+    /// `_` matches any value without binding it
+    Wildcard(Node),
+
+    /// Only in `match`, equal values match
+    Literal(Literal),
+
+    /// Only in `match`, `1..5` matches numbers from 1 to 5
+    Range(Box<RangePattern>),
+
+    /// `[a, [b, c], ...rest]`
+    Sequence(Box<SequencePattern>),
+
+    /// `{x, y: pattern}` takes the values of the keys of a hashmap
+    Hashmap(Box<HashmapPattern>),
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RangePattern {
+    pub node: Node,
+    pub start: f64,
+    pub end: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SequencePattern {
+    pub node: Node,
+    pub elements: Vec<Pattern>,
+
+    /// Collects the items left into a sequence
+    pub rest: Option<Identifier>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HashmapPattern {
+    pub node: Node,
+
+    /// Keys with the pattern of their value, `{x}` is short for `{x: x}`
+    pub entries: Vec<(Identifier, Pattern)>,
+}
+
+/// `match value { pattern if guard => expr, ... }`, the first matching arm is evaluated
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MatchExpression {
+    pub node: Node,
+    pub discriminant: Expression,
+    pub arms: Vec<MatchArm>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MatchArm {
+    pub node: Node,
+    pub pattern: Pattern,
+    pub guard: Option<Expression>,
+    pub body: Expression,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub enum Expression {
     BinaryExpression(Box<BinaryExpression>),
@@ -161,6 +294,10 @@ pub enum Expression {
     MemberExpression(Box<MemberExpression>),
     SequenceExpression(Box<SequenceExpression>),
     WordExpression(Box<WordExpression>),
+
+    /// Anonymous function, its `id` is empty
+    FunctionExpression(Box<FunctionDeclarator>),
+    MatchExpression(Box<MatchExpression>),
     Literal(Literal),
     Identifier(Identifier),
     None(None),
@@ -209,8 +346,18 @@ pub struct ConditionalExpression {
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct CallExpression {
     pub node: Node,
-    pub callee: String,
+    pub callee: Expression,
     pub arguments: Expression,
+
+    /// Arguments given by name like `f[1, b = 2]`, after the positional ones
+    pub named_arguments: Vec<NamedArgument>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NamedArgument {
+    pub node: Node,
+    pub name: String,
+    pub value: Expression,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -296,7 +443,7 @@ pub enum AssignOperator {
 
 //----------Display------------
 
-fn format_vec<T: fmt::Display>(vec: &Vec<T>, separator: &str) -> String {
+fn format_vec<T: fmt::Display>(vec: &[T], separator: &str) -> String {
     vec.iter()
         .map(|x| format!("{}", x))
         .collect::<Vec<String>>()
@@ -332,7 +479,9 @@ impl fmt::Display for Statement {
             Statement::BreakStatement(_) => write!(f, "break;"),
             Statement::YieldStatement(expr) => write!(f, "{}", expr),
             Statement::IfStatement(expr) => write!(f, "{}", expr),
+            Statement::TryStatement(expr) => write!(f, "{}", expr),
             Statement::ForStatement(expr) => write!(f, "{}", expr),
+            Statement::ForInStatement(expr) => write!(f, "{}", expr),
             Statement::WhileStatement(expr) => write!(f, "{}", expr),
             Statement::LoopStatement(expr) => write!(f, "{}", expr),
             Statement::BlockStatement(expr) => write!(f, "{{\n{}\n}}", format_vec(expr, "\n")),
@@ -351,7 +500,11 @@ impl fmt::Display for ImportStatement {
 
 impl fmt::Display for AssignStatement {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{} = {}", self.left, self.right)
+        write!(f, "{}", self.left)?;
+        for index in &self.indices {
+            write!(f, "[{}]", index)?;
+        }
+        write!(f, " = {}", self.right)
     }
 }
 
@@ -374,6 +527,19 @@ impl fmt::Display for ForStatement {
     }
 }
 
+impl fmt::Display for ForInStatement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "for ({} {} {}) {{\n{}\n}}",
+            self.left,
+            if self.is_of { "of" } else { "in" },
+            self.right,
+            format_vec(&self.body, "\n")
+        )
+    }
+}
+
 impl fmt::Display for WhileStatement {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
@@ -409,7 +575,7 @@ impl fmt::Display for VariableDeclarator {
             f,
             "let {} {} {}",
             self.id,
-            if self.is_formula == true { ":=" } else { "=" },
+            if self.is_formula { ":=" } else { "=" },
             self.init
         )
     }
@@ -462,6 +628,28 @@ impl fmt::Display for IfStatement {
     }
 }
 
+impl fmt::Display for TryStatement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "try {{\n{}\n}}", format_vec(&self.block, "\n"))?;
+
+        if let Some(handler) = &self.handler {
+            write!(f, " catch ")?;
+
+            if let Some(param) = &self.param {
+                write!(f, "{} ", param)?;
+            }
+
+            write!(f, "{{\n{}\n}}", format_vec(handler, "\n"))?;
+        }
+
+        if let Some(finalizer) = &self.finalizer {
+            write!(f, " finally {{\n{}\n}}", format_vec(finalizer, "\n"))?;
+        }
+
+        Ok(())
+    }
+}
+
 impl fmt::Display for Expression {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -474,6 +662,8 @@ impl fmt::Display for Expression {
             Expression::CallExpression(expr) => write!(f, "({})", expr),
             Expression::MemberExpression(expr) => write!(f, "({})", expr),
             Expression::WordExpression(expr) => write!(f, "({})", expr),
+            Expression::FunctionExpression(expr) => write!(f, "({})", expr),
+            Expression::MatchExpression(expr) => write!(f, "({})", expr),
             Expression::SequenceExpression(expr) => {
                 let len = expr.expressions.len();
                 let mut k = 0;
@@ -504,6 +694,64 @@ impl fmt::Display for Identifier {
     }
 }
 
+impl fmt::Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Pattern::Identifier(id) => write!(f, "{}", id),
+            Pattern::Wildcard(_) => write!(f, "_"),
+            Pattern::Literal(literal) => write!(f, "{}", literal),
+            Pattern::Range(range) => write!(f, "{}..{}", range.start, range.end),
+            Pattern::Sequence(pattern) => {
+                write!(f, "[{}", format_vec(&pattern.elements, ", "))?;
+
+                if let Some(rest) = &pattern.rest {
+                    if !pattern.elements.is_empty() {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "...{}", rest)?;
+                }
+
+                write!(f, "]")
+            }
+            Pattern::Hashmap(pattern) => {
+                let entries = pattern
+                    .entries
+                    .iter()
+                    .map(|(key, value)| match value {
+                        Pattern::Identifier(id) if id.name == key.name => key.to_string(),
+                        value => format!("{}: {}", key, value),
+                    })
+                    .collect::<Vec<String>>();
+
+                write!(f, "{{{}}}", entries.join(", "))
+            }
+        }
+    }
+}
+
+impl fmt::Display for MatchExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "match {} {{\n{}\n}}",
+            self.discriminant,
+            format_vec(&self.arms, ",\n")
+        )
+    }
+}
+
+impl fmt::Display for MatchArm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.pattern)?;
+
+        if let Some(guard) = &self.guard {
+            write!(f, " if {}", guard)?;
+        }
+
+        write!(f, " => {}", self.body)
+    }
+}
+
 impl fmt::Display for BinaryExpression {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}{}{}", self.left, self.operator, self.right)
@@ -528,7 +776,36 @@ impl fmt::Display for ConditionalExpression {
 
 impl fmt::Display for CallExpression {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}[{}]", self.callee, self.arguments)
+        // The brackets of the call are the brackets of its argument sequence
+        let mut arguments = match &self.arguments {
+            Expression::SequenceExpression(sequence) => format_vec(&sequence.expressions, ", "),
+            arguments => arguments.to_string(),
+        };
+
+        if !self.named_arguments.is_empty() {
+            if !arguments.is_empty() {
+                arguments += ", ";
+            }
+            arguments += &format_vec(&self.named_arguments, ", ");
+        }
+
+        write!(f, "{}[{}]", self.callee, arguments)
+    }
+}
+
+impl fmt::Display for NamedArgument {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} = {}", self.name, self.value)
+    }
+}
+
+impl fmt::Display for Parameter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.default {
+            _ if self.is_rest => write!(f, "...{}", self.name),
+            Some(default) => write!(f, "{} = {}", self.name, default),
+            None => write!(f, "{}", self.name),
+        }
     }
 }
 
@@ -0,0 +1,418 @@
+use std::{fmt, str::FromStr};
+
+use serde_json::{json, Value};
+use symboscript_types::{
+    interpreter::{RuntimeError, TraceFrame},
+    parser::ParseError,
+};
+
+use crate::render::{render, LineIndex};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+    Note,
+    Help,
+}
+
+impl Severity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Note => "note",
+            Severity::Help => "help",
+        }
+    }
+
+    /// SARIF has no `help` level
+    fn sarif_level(&self) -> &'static str {
+        match self {
+            Severity::Help => "note",
+            severity => severity.as_str(),
+        }
+    }
+}
+
+/// Span of the source with an optional message
+#[derive(Clone, Debug, PartialEq)]
+pub struct Label {
+    pub start: usize,
+    pub end: usize,
+    pub message: Option<String>,
+}
+
+/// Replacement of a span that fixes the diagnostic
+#[derive(Clone, Debug, PartialEq)]
+pub struct Suggestion {
+    pub message: String,
+    pub start: usize,
+    pub end: usize,
+    pub replacement: String,
+}
+
+/// Error or warning about a source file, rendered with `print_diagnostics`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+
+    /// Stable identifier of the kind of the diagnostic, like `expected-token`
+    pub code: Option<String>,
+    pub message: String,
+
+    /// Path and text of the source the spans point into
+    pub path: String,
+    pub source: String,
+
+    pub primary: Label,
+
+    /// Related spans, like a declaration for an error at a use
+    pub secondary: Vec<Label>,
+    pub notes: Vec<String>,
+    pub suggestions: Vec<Suggestion>,
+
+    /// Calls that led to a runtime error, outermost first
+    pub traceback: Vec<TraceFrame>,
+}
+
+impl Diagnostic {
+    pub fn new(
+        severity: Severity,
+        path: &str,
+        source: &str,
+        message: &str,
+        start: usize,
+        end: usize,
+    ) -> Self {
+        Self {
+            severity,
+            code: None,
+            message: message.to_owned(),
+            path: path.to_owned(),
+            source: source.to_owned(),
+            primary: Label {
+                start,
+                end,
+                message: None,
+            },
+            secondary: vec![],
+            notes: vec![],
+            suggestions: vec![],
+            traceback: vec![],
+        }
+    }
+
+    pub fn error(path: &str, source: &str, message: &str, start: usize, end: usize) -> Self {
+        Self::new(Severity::Error, path, source, message, start, end)
+    }
+
+    pub fn with_code(mut self, code: &str) -> Self {
+        self.code = Some(code.to_owned());
+        self
+    }
+
+    pub fn with_label(mut self, start: usize, end: usize, message: &str) -> Self {
+        self.secondary.push(Label {
+            start,
+            end,
+            message: Some(message.to_owned()),
+        });
+        self
+    }
+
+    pub fn with_note(mut self, note: &str) -> Self {
+        self.notes.push(note.to_owned());
+        self
+    }
+
+    pub fn with_suggestion(
+        mut self,
+        message: &str,
+        start: usize,
+        end: usize,
+        replacement: &str,
+    ) -> Self {
+        self.suggestions.push(Suggestion {
+            message: message.to_owned(),
+            start,
+            end,
+            replacement: replacement.to_owned(),
+        });
+        self
+    }
+
+    pub fn with_traceback(mut self, traceback: Vec<TraceFrame>) -> Self {
+        self.traceback = traceback;
+        self
+    }
+
+    pub fn from_parse_error(path: &str, source: &str, error: &ParseError) -> Self {
+        let diagnostic = Self::error(
+            path,
+            source,
+            &error.message,
+            error.node.start,
+            error.node.end,
+        )
+        .with_code(&error.code);
+
+        match &error.fix {
+            Some(fix) => diagnostic.with_suggestion(
+                &fix.message,
+                fix.node.start,
+                fix.node.end,
+                &fix.replacement,
+            ),
+            None => diagnostic,
+        }
+    }
+
+    /// Line and column of the offset, both counted from 1, columns are counted in characters.
+    /// It indexes the whole source, spans of the same diagnostic share one `LineIndex` instead
+    pub fn position(&self, offset: usize) -> (usize, usize) {
+        LineIndex::new(&self.source).char_position(offset)
+    }
+
+    /// JSON object of the diagnostic, printed as one line by the `json` format
+    pub fn to_json(&self) -> Value {
+        let index = LineIndex::new(&self.source);
+        let span = |label: &Label| {
+            let (line_start, column_start) = index.char_position(label.start);
+            let (line_end, column_end) = index.char_position(label.end);
+
+            json!({
+                "start": label.start,
+                "end": label.end,
+                "line_start": line_start,
+                "column_start": column_start,
+                "line_end": line_end,
+                "column_end": column_end,
+                "label": label.message,
+            })
+        };
+
+        json!({
+            "severity": self.severity.as_str(),
+            "code": self.code,
+            "message": self.message,
+            "path": self.path,
+            "primary": span(&self.primary),
+            "secondary": self.secondary.iter().map(span).collect::<Vec<_>>(),
+            "notes": self.notes,
+            "suggestions": self.suggestions.iter().map(|suggestion| json!({
+                "message": suggestion.message,
+                "span": span(&Label { start: suggestion.start, end: suggestion.end, message: None }),
+                "replacement": suggestion.replacement,
+            })).collect::<Vec<_>>(),
+            "traceback": self.traceback.iter().map(|frame| json!({
+                "function": frame.function,
+                "path": frame.path,
+                "line": frame.line,
+                "column": frame.column,
+                "code": frame.code,
+            })).collect::<Vec<_>>(),
+        })
+    }
+
+    fn sarif_region(index: &LineIndex, start: usize, end: usize) -> Value {
+        let (start_line, start_column) = index.char_position(start);
+        let (end_line, end_column) = index.char_position(end);
+
+        json!({
+            "startLine": start_line,
+            "startColumn": start_column,
+            "endLine": end_line,
+            "endColumn": end_column,
+        })
+    }
+
+    fn sarif_location(&self, index: &LineIndex, label: &Label) -> Value {
+        let mut location = json!({
+            "physicalLocation": {
+                "artifactLocation": { "uri": self.path },
+                "region": Self::sarif_region(index, label.start, label.end),
+            }
+        });
+
+        if let Some(message) = &label.message {
+            location["message"] = json!({ "text": message });
+        }
+
+        location
+    }
+
+    /// SARIF result, notes are appended to the message since results have a single one
+    fn to_sarif_result(&self) -> Value {
+        let mut text = self.message.clone();
+        for note in &self.notes {
+            text += &format!("\nnote: {note}");
+        }
+
+        let index = LineIndex::new(&self.source);
+        let mut result = json!({
+            "level": self.severity.sarif_level(),
+            "message": { "text": text },
+            "locations": [self.sarif_location(&index, &self.primary)],
+            "relatedLocations": self.secondary.iter().enumerate().map(|(id, label)| {
+                let mut location = self.sarif_location(&index, label);
+                location["id"] = json!(id);
+                location
+            }).collect::<Vec<_>>(),
+            "fixes": self.suggestions.iter().map(|suggestion| json!({
+                "description": { "text": suggestion.message },
+                "artifactChanges": [{
+                    "artifactLocation": { "uri": self.path },
+                    "replacements": [{
+                        "deletedRegion": Self::sarif_region(&index, suggestion.start, suggestion.end),
+                        "insertedContent": { "text": suggestion.replacement },
+                    }],
+                }],
+            })).collect::<Vec<_>>(),
+        });
+
+        if let Some(code) = &self.code {
+            result["ruleId"] = json!(code);
+        }
+
+        // SARIF stacks start with the innermost frame
+        if !self.traceback.is_empty() {
+            result["stacks"] = json!([{
+                "frames": self.traceback.iter().rev().map(|frame| json!({
+                    "location": {
+                        "physicalLocation": {
+                            "artifactLocation": { "uri": frame.path },
+                            "region": { "startLine": frame.line, "startColumn": frame.column },
+                        },
+                        "message": { "text": frame.function },
+                    },
+                })).collect::<Vec<_>>(),
+            }]);
+        }
+
+        result
+    }
+
+    fn print_human(&self) {
+        eprint!("{}", render(self));
+    }
+}
+
+impl From<&RuntimeError> for Diagnostic {
+    fn from(error: &RuntimeError) -> Self {
+        let diagnostic = Self::error(
+            &error.path,
+            &error.source,
+            &error.message,
+            error.node.start,
+            error.node.end,
+        )
+        .with_code(&error.code)
+        .with_traceback(error.traceback.clone());
+
+        let diagnostic = error
+            .labels
+            .iter()
+            .fold(diagnostic, |diagnostic, (node, message)| {
+                diagnostic.with_label(node.start, node.end, message)
+            });
+
+        error
+            .notes
+            .iter()
+            .fold(diagnostic, |diagnostic, note| diagnostic.with_note(note))
+    }
+}
+
+/// Diagnostics of a runtime error and of the errors reported with it
+pub fn runtime_diagnostics(error: &RuntimeError) -> Vec<Diagnostic> {
+    std::iter::once(error)
+        .chain(&error.related)
+        .map(Diagnostic::from)
+        .collect()
+}
+
+/// Output of the diagnostics of a binary, selected with `--message-format`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MessageFormat {
+    /// Colored text for terminals
+    #[default]
+    Human,
+
+    /// One JSON object per line
+    Json,
+
+    /// A single SARIF 2.1.0 log with all diagnostics
+    Sarif,
+}
+
+impl FromStr for MessageFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "human" => Ok(MessageFormat::Human),
+            "json" => Ok(MessageFormat::Json),
+            "sarif" => Ok(MessageFormat::Sarif),
+            _ => Err(format!(
+                "unknown message format `{s}`, expected human, json or sarif"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for MessageFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MessageFormat::Human => write!(f, "human"),
+            MessageFormat::Json => write!(f, "json"),
+            MessageFormat::Sarif => write!(f, "sarif"),
+        }
+    }
+}
+
+/// SARIF log of the diagnostics, columns are counted in characters like in the other formats
+pub fn to_sarif(diagnostics: &[Diagnostic]) -> Value {
+    let mut rules = diagnostics
+        .iter()
+        .filter_map(|diagnostic| diagnostic.code.clone())
+        .collect::<Vec<_>>();
+    rules.sort();
+    rules.dedup();
+
+    json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "symboscript",
+                    "informationUri": "https://github.com/artegoser/SymboScript",
+                    "rules": rules.iter().map(|id| json!({ "id": id })).collect::<Vec<_>>(),
+                }
+            },
+            "columnKind": "unicodeCodePoints",
+            "results": diagnostics.iter().map(Diagnostic::to_sarif_result).collect::<Vec<_>>(),
+        }]
+    })
+}
+
+/// Prints the diagnostics to stderr, so they don't mix with the output of the program
+pub fn print_diagnostics(diagnostics: &[Diagnostic], format: MessageFormat) {
+    match format {
+        MessageFormat::Human => diagnostics.iter().for_each(Diagnostic::print_human),
+        MessageFormat::Json => {
+            for diagnostic in diagnostics {
+                eprintln!("{}", diagnostic.to_json());
+            }
+        }
+        MessageFormat::Sarif => eprintln!("{:#}", to_sarif(diagnostics)),
+    }
+}
+
+/// Prints the diagnostics and exits
+pub fn report_diagnostics(diagnostics: &[Diagnostic], format: MessageFormat) -> ! {
+    print_diagnostics(diagnostics, format);
+
+    std::process::exit(1);
+}
@@ -1,22 +1,30 @@
-use std::cmp::max;
+pub mod diagnostic;
+pub mod render;
+
+#[cfg(test)]
+mod tests;
 
 use colored::Colorize;
-use symboscript_types::lexer::{Token, TokenKind::*};
+use diagnostic::{print_diagnostics, report_diagnostics, Diagnostic, MessageFormat};
+use symboscript_types::{
+    lexer::{Token, TokenKind::*},
+    parser::ParseError,
+};
 
-pub fn output_tokens_colored(text: &str, tokens: &Vec<Token>, show_tokens: Option<bool>) {
+pub fn output_tokens_colored(text: &str, tokens: &[Token], show_tokens: Option<bool>) {
     let show_tokens = show_tokens.unwrap_or(false);
     let mut last_start;
     let mut last_end = 0;
 
     for token in tokens {
         last_start = token.start;
-        print!("{}", text[last_end..last_start].to_string());
+        print!("{}", &text[last_end..last_start]);
         last_end = token.end;
 
         let s = if show_tokens {
-            format!("<{}>", text[token.start..token.end].to_string())
+            format!("<{}>", &text[token.start..token.end])
         } else {
-            format!("{}", text[token.start..token.end].to_string())
+            text[token.start..token.end].to_string()
         };
 
         print!("{}", {
@@ -32,7 +40,7 @@ pub fn output_tokens_colored(text: &str, tokens: &Vec<Token>, show_tokens: Optio
                 LParen | RParen | LAngle | RAngle => s.cyan(),
 
                 If | Else | While | For | Loop | Let | Return | Break | Continue | Function
-                | True | False | In => s.magenta(),
+                | True | False | In | Throw | Try | Catch | Finally => s.magenta(),
 
                 Str => s.truecolor(206, 145, 120),
 
@@ -43,66 +51,44 @@ pub fn output_tokens_colored(text: &str, tokens: &Vec<Token>, show_tokens: Optio
         });
     }
 
-    print!("{}", text[last_end..].to_string());
+    print!("{}", &text[last_end..]);
 
     println!();
 }
 
 pub fn report_error(path: &str, source: &str, error: &str, start: usize, end: usize) {
-    let line_start = max(source[..start].lines().count(), 1);
-    let line_end = max(source[..end].lines().count(), 1);
-
-    let mut column_start = start - source[..start].rfind('\n').unwrap_or(0);
-    let mut column_end = end - source[..end].rfind('\n').unwrap_or(0);
-
-    if line_start == 1 || line_end == 1 {
-        column_end += 1;
-        column_start += 1;
-    }
+    print_error(path, source, error, start, end);
 
-    if column_end < column_start {
-        column_end = source[..end].rfind('\n').unwrap_or(0);
-    }
+    std::process::exit(1);
+}
 
-    let near_text = source.lines().nth(line_end - 1).unwrap_or("").trim_end();
-
-    let line_n = format!("{line_end} |");
-
-    let error_pointer = (" ".repeat(column_start + line_n.len())
-        + "^".repeat(column_end - column_start).as_str())
-    .red()
-    .bold();
-
-    let error_pointer_text = error
-        .replace(
-            "\n",
-            &format!(
-                "\n{} ",
-                " ".repeat(column_end - column_start) + &" ".repeat(column_start + line_n.len())
-            ),
-        )
-        .red()
-        .bold();
-
-    let file_src = format!(
-        "--> {}:{}:{}-{}:{} ({start} - {end})",
-        path, line_start, column_start, line_end, column_end
-    );
+/// Prints every syntax error in the format and exits
+pub fn report_parse_errors(
+    path: &str,
+    source: &str,
+    errors: &[ParseError],
+    format: MessageFormat,
+) -> ! {
+    let diagnostics = errors
+        .iter()
+        .map(|error| Diagnostic::from_parse_error(path, source, error))
+        .collect::<Vec<_>>();
+
+    report_diagnostics(&diagnostics, format);
+}
 
-    println!("{}", file_src.blue().bold());
+pub fn print_parse_errors(path: &str, source: &str, errors: &[ParseError]) {
+    let diagnostics = errors
+        .iter()
+        .map(|error| Diagnostic::from_parse_error(path, source, error))
+        .collect::<Vec<_>>();
 
-    for i in line_start..line_end {
-        println!(
-            "{} {}",
-            format!("{} |", i).blue().bold(),
-            source.lines().nth(i - 1).unwrap_or("")
-        );
-    }
+    print_diagnostics(&diagnostics, MessageFormat::Human);
+}
 
-    println!(
-        "{} {near_text}\n{error_pointer} {error_pointer_text}",
-        line_n.to_string().blue().bold(),
+pub fn print_error(path: &str, source: &str, error: &str, start: usize, end: usize) {
+    eprint!(
+        "{}",
+        render::render(&Diagnostic::error(path, source, error, start, end))
     );
-
-    std::process::exit(1);
 }
@@ -0,0 +1,301 @@
+use std::collections::BTreeMap;
+
+use colored::{Color, Colorize};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+use symboscript_types::interpreter::TraceFrame;
+
+use crate::diagnostic::{Diagnostic, Severity};
+
+/// Spans longer than this show only their first and last lines
+const MAX_SPAN_LINES: usize = 4;
+
+/// Columns a tab is drawn with
+const TAB_WIDTH: usize = 4;
+
+/// Repeated traceback lines of a recursion shown before they are counted
+const MAX_REPEATED_FRAMES: usize = 3;
+
+/// Byte offsets of the starts of lines, offsets are turned into lines by binary search
+pub struct LineIndex<'a> {
+    source: &'a str,
+    starts: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+    pub fn new(source: &'a str) -> Self {
+        let starts = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+
+        Self { source, starts }
+    }
+
+    /// Line of the offset, counted from 0
+    pub fn line(&self, offset: usize) -> usize {
+        match self.starts.binary_search(&offset) {
+            Ok(line) => line,
+            Err(next) => next - 1,
+        }
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.starts.len()
+    }
+
+    /// Text of the line without its line break
+    pub fn line_text(&self, line: usize) -> &'a str {
+        let start = self.starts[line];
+        let end = self
+            .starts
+            .get(line + 1)
+            .map_or(self.source.len(), |next| next - 1);
+
+        self.source[start..end].trim_end_matches('\r')
+    }
+
+    /// Text of the line before the offset
+    fn before(&self, offset: usize) -> &'a str {
+        let offset = self.clamp(offset);
+        &self.source[self.starts[self.line(offset)]..offset]
+    }
+
+    /// Line and column of the offset, both counted from 1, columns are counted in grapheme clusters
+    pub fn position(&self, offset: usize) -> (usize, usize) {
+        let offset = self.clamp(offset);
+        (
+            self.line(offset) + 1,
+            self.before(offset).graphemes(true).count() + 1,
+        )
+    }
+
+    /// Like `position`, but columns are counted in Unicode code points
+    pub fn char_position(&self, offset: usize) -> (usize, usize) {
+        let offset = self.clamp(offset);
+        (
+            self.line(offset) + 1,
+            self.before(offset).chars().count() + 1,
+        )
+    }
+
+    /// Traceback line of the offset with the trimmed source line, columns are counted like in `char_position`
+    pub fn trace_frame(&self, function: &str, path: &str, offset: usize) -> TraceFrame {
+        let (line, column) = self.char_position(offset);
+
+        TraceFrame {
+            function: function.to_owned(),
+            path: path.to_owned(),
+            line,
+            column,
+            code: self.line_text(line - 1).trim().to_owned(),
+        }
+    }
+
+    /// Offset inside the source and on a character boundary
+    fn clamp(&self, offset: usize) -> usize {
+        let mut offset = offset.min(self.source.len());
+        while !self.source.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        offset
+    }
+}
+
+/// Terminal cells the text takes
+fn width(text: &str) -> usize {
+    text.graphemes(true)
+        .map(|g| if g == "\t" { TAB_WIDTH } else { g.width() })
+        .sum()
+}
+
+/// Count of the repeated traceback lines that are left out
+fn repeated_frames(repeated: usize) -> String {
+    match repeated.saturating_sub(MAX_REPEATED_FRAMES - 1) {
+        0 => String::new(),
+        1 => "  [Previous line repeated 1 more time]\n".to_owned(),
+        n => format!("  [Previous line repeated {n} more times]\n"),
+    }
+}
+
+/// Underline of a label on one line, columns are terminal cells
+struct Underline {
+    from: usize,
+    to: usize,
+    primary: bool,
+
+    /// Message of the label, only on the last line of the label
+    message: Option<String>,
+}
+
+/// Diagnostic as colored text with the source lines of its labels
+pub fn render(diagnostic: &Diagnostic) -> String {
+    let index = LineIndex::new(&diagnostic.source);
+    let color = match diagnostic.severity {
+        Severity::Error => Color::Red,
+        Severity::Warning => Color::Yellow,
+        Severity::Note | Severity::Help => Color::Cyan,
+    };
+
+    let labels = std::iter::once((&diagnostic.primary, true))
+        .chain(diagnostic.secondary.iter().map(|label| (label, false)));
+
+    let mut lines: BTreeMap<usize, Vec<Underline>> = BTreeMap::new();
+
+    // The end of a source ending with a line break is shown at the end of its last line
+    let source = &diagnostic.source;
+    let clamp = |offset: usize| match source.strip_suffix('\n') {
+        Some(trimmed) if offset > trimmed.len() => trimmed.len(),
+        _ => index.clamp(offset),
+    };
+
+    for (label, primary) in labels {
+        let start = clamp(label.start);
+        let end = clamp(label.end.max(label.start));
+        let first = index.line(start);
+        // A span ending with a line break doesn't reach into the next line
+        let last = if end > start {
+            index.line(end - 1)
+        } else {
+            first
+        };
+
+        for line in first..=last {
+            // Middle lines of long spans are left out
+            if last - first > MAX_SPAN_LINES && line > first + 1 && line + 1 < last {
+                continue;
+            }
+
+            let text = index.line_text(line);
+
+            // Blank lines inside a span are shown without an underline
+            if line != first && line != last && text.trim().is_empty() {
+                lines.entry(line).or_default();
+                continue;
+            }
+
+            let indent = width(&text[..text.len() - text.trim_start().len()]);
+
+            let from = if line == first {
+                width(index.before(start))
+            } else {
+                indent
+            };
+            let to = if line == last && index.line(end) == last {
+                width(index.before(end))
+            } else {
+                width(text)
+            };
+
+            lines.entry(line).or_default().push(Underline {
+                from,
+                // Empty spans, like the end of the file, are still pointed at
+                to: to.max(from + 1),
+                primary,
+                message: label.message.clone().filter(|_| line == last),
+            });
+        }
+    }
+
+    let gutter = lines
+        .keys()
+        .last()
+        .map_or(1, |line| (line + 1).to_string().len());
+    let bar = format!("{} |", " ".repeat(gutter)).blue().bold();
+
+    let header = match &diagnostic.code {
+        Some(code) => format!("{}[{code}]", diagnostic.severity.as_str()),
+        None => diagnostic.severity.as_str().to_owned(),
+    };
+    let (line, column) = index.position(clamp(diagnostic.primary.start));
+
+    let mut out = String::new();
+
+    // A traceback of the error alone would only repeat it
+    if diagnostic.traceback.len() > 1 {
+        out += &format!("{}\n", "Traceback (most recent call last):".bold());
+
+        let mut repeated = 0;
+
+        for (i, frame) in diagnostic.traceback.iter().enumerate() {
+            if i > 0 && diagnostic.traceback[i - 1] == *frame {
+                repeated += 1;
+            } else {
+                out += &repeated_frames(repeated);
+                repeated = 0;
+            }
+
+            if repeated < MAX_REPEATED_FRAMES {
+                out += &format!(
+                    "  File \"{}\", line {}, in {}\n    {}\n",
+                    frame.path, frame.line, frame.function, frame.code
+                );
+            }
+        }
+
+        out += &repeated_frames(repeated);
+    }
+
+    out += &format!(
+        "{}{} {}\n",
+        header.color(color).bold(),
+        ":".bold(),
+        diagnostic.message.bold()
+    );
+    out += &format!(
+        "{}{} {}:{line}:{column}\n",
+        " ".repeat(gutter),
+        "-->".blue().bold(),
+        diagnostic.path
+    );
+    out += &format!("{bar}\n");
+
+    let mut previous = None;
+
+    for (line, underlines) in lines.iter_mut() {
+        if previous.is_some_and(|previous| previous + 1 < *line) {
+            out += &format!("{}\n", "...".blue().bold());
+        }
+        previous = Some(*line);
+
+        let text = index.line_text(*line).replace('\t', &" ".repeat(TAB_WIDTH));
+        out += &format!(
+            "{} {text}\n",
+            format!("{:>gutter$} |", line + 1).blue().bold()
+        );
+
+        underlines.sort_by_key(|underline| (underline.from, !underline.primary));
+
+        for underline in underlines.iter() {
+            let (marker, label_color) = if underline.primary {
+                ("^", color)
+            } else {
+                ("-", Color::Blue)
+            };
+
+            let mut pointer =
+                " ".repeat(underline.from) + &marker.repeat(underline.to - underline.from);
+            if let Some(message) = &underline.message {
+                pointer += &format!(" {message}");
+            }
+
+            out += &format!("{bar} {}\n", pointer.color(label_color).bold());
+        }
+    }
+
+    for note in &diagnostic.notes {
+        out += &format!("{} {} {note}\n", " ".repeat(gutter), "= note:".bold());
+    }
+
+    for suggestion in &diagnostic.suggestions {
+        out += &format!(
+            "{} {} {}: `{}`\n",
+            " ".repeat(gutter),
+            "= help:".bold(),
+            suggestion.message,
+            suggestion.replacement
+        );
+    }
+
+    out
+}
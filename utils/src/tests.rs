@@ -0,0 +1,242 @@
+pub mod diagnostic_tests {
+    use crate::diagnostic::{to_sarif, Diagnostic, MessageFormat};
+
+    fn diagnostic() -> Diagnostic {
+        Diagnostic::error(
+            "test.syms",
+            "let a = 1;\nlet é = b;",
+            "Variable `b` not found",
+            20,
+            21,
+        )
+        .with_code("undefined-variable")
+        .with_label(4, 5, "declared here")
+        .with_note("variables must be declared before use")
+        .with_suggestion("use the declared variable", 20, 21, "a")
+    }
+
+    #[test]
+    fn positions() {
+        let diagnostic = diagnostic();
+
+        assert_eq!(diagnostic.position(0), (1, 1));
+        assert_eq!(diagnostic.position(11), (2, 1));
+        // `é` is two bytes but one column
+        assert_eq!(diagnostic.position(20), (2, 9));
+    }
+
+    #[test]
+    fn json() {
+        let json = diagnostic().to_json();
+
+        assert_eq!(json["severity"], "error");
+        assert_eq!(json["code"], "undefined-variable");
+        assert_eq!(json["primary"]["line_start"], 2);
+        assert_eq!(json["primary"]["column_end"], 10);
+        assert_eq!(json["secondary"][0]["label"], "declared here");
+        assert_eq!(json["suggestions"][0]["replacement"], "a");
+    }
+
+    #[test]
+    fn sarif() {
+        let sarif = to_sarif(&[diagnostic()]);
+        let result = &sarif["runs"][0]["results"][0];
+
+        assert_eq!(sarif["version"], "2.1.0");
+        assert_eq!(
+            sarif["runs"][0]["tool"]["driver"]["rules"][0]["id"],
+            "undefined-variable"
+        );
+        assert_eq!(result["level"], "error");
+        assert_eq!(
+            result["locations"][0]["physicalLocation"]["region"]["startColumn"],
+            9
+        );
+        assert_eq!(
+            result["relatedLocations"][0]["message"]["text"],
+            "declared here"
+        );
+        assert_eq!(
+            result["fixes"][0]["artifactChanges"][0]["replacements"][0]["insertedContent"]["text"],
+            "a"
+        );
+    }
+
+    #[test]
+    fn from_runtime_error() {
+        use symboscript_types::{interpreter::RuntimeError, parser::Node};
+
+        let error = RuntimeError {
+            code: "formula-cycle".to_owned(),
+            message: "Formulas depend on each other: `a` -> `b` -> `a`".to_owned(),
+            node: Node::new(11, 21),
+            path: "test.syms".to_owned(),
+            source: "let a := b;\nlet b := a;".into(),
+            traceback: vec![],
+            labels: vec![(Node::new(0, 10), "`a` is declared here".to_owned())],
+            notes: vec!["formulas are evaluated when they are read".to_owned()],
+            related: vec![],
+        };
+        let diagnostic = Diagnostic::from(&error);
+
+        assert_eq!(diagnostic.code.as_deref(), Some("formula-cycle"));
+        assert_eq!(diagnostic.secondary[0].start, 0);
+        assert_eq!(
+            diagnostic.secondary[0].message.as_deref(),
+            Some("`a` is declared here")
+        );
+        assert_eq!(diagnostic.notes, error.notes);
+    }
+
+    #[test]
+    fn message_formats() {
+        assert_eq!("sarif".parse(), Ok(MessageFormat::Sarif));
+        assert!("xml".parse::<MessageFormat>().is_err());
+    }
+}
+
+pub mod render_tests {
+    use crate::{
+        diagnostic::Diagnostic,
+        render::{render, LineIndex},
+    };
+
+    fn plain(diagnostic: &Diagnostic) -> String {
+        colored::control::set_override(false);
+        render(diagnostic)
+    }
+
+    #[test]
+    fn line_index() {
+        let index = LineIndex::new("ab\r\nc\n\nd");
+
+        assert_eq!(index.line_count(), 4);
+        assert_eq!(index.line(0), 0);
+        assert_eq!(index.line(4), 1);
+        assert_eq!(index.line(6), 2);
+        assert_eq!(index.line_text(0), "ab");
+        assert_eq!(index.position(7), (4, 1));
+
+        // `e` with a combining accent is one grapheme of two characters
+        let index = LineIndex::new("e\u{301}x");
+        assert_eq!(index.position(3), (1, 2));
+        assert_eq!(index.char_position(3), (1, 3));
+
+        // Offsets inside a character go back to its start
+        let frame = LineIndex::new("let é = 1;\n  f[é];").trace_frame("<main>", "test", 5);
+        assert_eq!((frame.line, frame.column), (1, 5));
+        assert_eq!(frame.code, "let é = 1;");
+    }
+
+    #[test]
+    fn single_line() {
+        let diagnostic = Diagnostic::error("test", "let 数 = a;", "Variable `a` not found", 10, 11)
+            .with_code("undefined-variable");
+
+        assert_eq!(
+            plain(&diagnostic),
+            "error[undefined-variable]: Variable `a` not found
+ --> test:1:9
+  |
+1 | let 数 = a;
+  |          ^
+"
+        );
+    }
+
+    #[test]
+    fn labels_and_notes() {
+        let source = "let a = 1;\nlet b = 2;\nlet c = a + d;\n";
+        let diagnostic = Diagnostic::error("test", source, "Variable `d` not found", 34, 35)
+            .with_label(4, 5, "similar variable declared here")
+            .with_label(30, 31, "used here")
+            .with_note("variables are declared with `let`")
+            .with_suggestion("use the declared variable", 34, 35, "a");
+
+        assert_eq!(
+            plain(&diagnostic),
+            "error: Variable `d` not found
+ --> test:3:13
+  |
+1 | let a = 1;
+  |     - similar variable declared here
+...
+3 | let c = a + d;
+  |         - used here
+  |             ^
+  = note: variables are declared with `let`
+  = help: use the declared variable: `a`
+"
+        );
+    }
+
+    #[test]
+    fn multi_line() {
+        let source = "fn f[] {\n  a;\n\n  b;\n}";
+        let diagnostic = Diagnostic::error("test", source, "Bad function", 0, source.len());
+
+        assert_eq!(
+            plain(&diagnostic),
+            "error: Bad function
+ --> test:1:1
+  |
+1 | fn f[] {
+  | ^^^^^^^^
+2 |   a;
+  |   ^^
+3 | 
+4 |   b;
+  |   ^^
+5 | }
+  | ^
+"
+        );
+    }
+
+    #[test]
+    fn end_of_file() {
+        let diagnostic = Diagnostic::error("test", "let a = 1\n", "Expected ;", 10, 10);
+
+        assert_eq!(
+            plain(&diagnostic),
+            "error: Expected ;
+ --> test:1:10
+  |
+1 | let a = 1
+  |          ^
+"
+        );
+    }
+
+    #[test]
+    fn traceback() {
+        let source = "fn f[n] {\n  return f[n - 1];\n}\nf[9];";
+        let index = LineIndex::new(source);
+        let call = index.trace_frame("f", "test", 19);
+        let diagnostic = Diagnostic::error("test", source, "Too deep", 19, 27).with_traceback(
+            std::iter::once(index.trace_frame("<main>", "test", 31))
+                .chain(std::iter::repeat_n(call, 5))
+                .collect(),
+        );
+
+        assert_eq!(
+            plain(&diagnostic),
+            "Traceback (most recent call last):
+  File \"test\", line 4, in <main>
+    f[9];
+  File \"test\", line 2, in f
+    return f[n - 1];
+  File \"test\", line 2, in f
+    return f[n - 1];
+  File \"test\", line 2, in f
+    return f[n - 1];
+  [Previous line repeated 2 more times]
+error: Too deep
+ --> test:2:10
+  |
+2 |   return f[n - 1];
+  |          ^^^^^^^^
+"
+        );
+    }
+}